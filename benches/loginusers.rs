@@ -0,0 +1,74 @@
+//! Compares looking up the users of a large loginusers.vdf with and without a [`Document::index`].
+
+use std::fmt::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use diverter::vdf::{scan_parse, Document, ExprId, LoginUser, Value};
+
+/// Generates a loginusers.vdf with the given number of users.
+fn loginusers(users: u64) -> Vec<u8> {
+    let mut source = String::from("\"users\"\n{\n");
+    for i in 0..users {
+        let _ = write!(source, "\t\"{}\"\n\t{{\n", 76561197960265728 + i);
+        for (key, value) in [
+            ("AccountName", format!("user_{i}")),
+            ("PersonaName", format!("User {i}")),
+            ("RememberPassword", "1".to_owned()),
+            ("AllowAutoLogin", "1".to_owned()),
+            ("MostRecent", "0".to_owned()),
+            ("Timestamp", (1_700_000_000 + i).to_string()),
+        ] {
+            let _ = writeln!(source, "\t\t\"{key}\"\t\t\"{value}\"");
+        }
+        source.push_str("\t}\n");
+    }
+    source.push_str("}\n");
+    source.into_bytes()
+}
+
+/// Looks up each user's account name with the [`Document`]'s lookups, which scan the whole document.
+fn account_names_linear(document: &Document) -> usize {
+    let users = document.subkeys(ExprId::ROOT, b"users").unwrap();
+    document
+        .children(users)
+        .filter_map(|user| match user.value {
+            Value::Subkeys(sub) => document.value_str(sub, b"AccountName"),
+            _ => None,
+        })
+        .count()
+}
+
+/// Looks up each user's account name with a [`Document::index`].
+fn account_names_indexed(document: &Document) -> usize {
+    let index = document.index();
+    let users = index.subkeys(ExprId::ROOT, b"users").unwrap();
+    index
+        .children(users)
+        .filter_map(|user| match user.value {
+            Value::Subkeys(sub) => index.value_str(sub, b"AccountName"),
+            _ => None,
+        })
+        .count()
+}
+
+fn lookups(c: &mut Criterion) {
+    let source = loginusers(500);
+    let document = scan_parse(&source).unwrap();
+    assert_eq!(account_names_linear(&document), 500);
+    assert_eq!(account_names_indexed(&document), 500);
+
+    let mut group = c.benchmark_group("500 accounts");
+    group.bench_function("linear", |b| {
+        b.iter(|| account_names_linear(black_box(&document)))
+    });
+    group.bench_function("indexed", |b| {
+        b.iter(|| account_names_indexed(black_box(&document)))
+    });
+    group.bench_function("LoginUser::from_vdf", |b| {
+        b.iter(|| LoginUser::from_vdf(black_box(&document)).unwrap().count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, lookups);
+criterion_main!(benches);
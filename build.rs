@@ -1,7 +1,12 @@
 fn main() {
     println!("cargo:rerun-if-changed=src/windows.c");
+    // the C helpers are Windows-only, and Linux and macOS get by with the standard library.
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("windows") {
+        return;
+    }
     cc::Build::new()
         .file("src/windows.c")
         .warnings_into_errors(true)
         .compile("windowsutil");
+    println!("cargo:rustc-link-lib=version");
 }
@@ -0,0 +1,304 @@
+//! A platform-agnostic interface to the Steam client.
+
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, Result, Steam, Username};
+
+/// The Steam client operations every supported platform provides, so callers needn't care which one they run on.
+///
+/// Implemented by [`Steam`] on each platform.
+pub trait SteamBackend {
+    /// Gets the Steam user that Steam will attempt to automatically log into.
+    fn get_auto_login_user(&self) -> Result<Username>;
+
+    /// Gets the auto-login user's value as is, without validating it as a [`Username`], e.g. for diagnosing an
+    /// invalid value.
+    fn get_auto_login_user_raw(&self) -> Result<Vec<u8>>;
+
+    /// Sets the Steam user that Steam will attempt to automatically log into.
+    fn set_auto_login_user(&self, username: Username) -> Result<()>;
+
+    /// Removes the auto-login user, so Steam asks which account to log into on its next launch.
+    ///
+    /// Succeeds if there's no auto-login user to begin with.
+    fn clear_auto_login_user(&self) -> Result<()>;
+
+    /// Sets the auto-login user like [`Self::set_auto_login_user`], and then [verifies](Self::verify_auto_login_user)
+    /// it holds.
+    fn set_auto_login_user_verified(&self, username: Username) -> Result<()> {
+        self.set_auto_login_user(username)?;
+        self.verify_auto_login_user(username)
+    }
+
+    /// Reads the auto-login user back, and fails with [`Error::AutoLoginUserMismatch`] if it isn't the given one.
+    ///
+    /// Usernames are compared case-insensitively.
+    fn verify_auto_login_user(&self, expected: Username) -> Result<()> {
+        let found = self.get_auto_login_user()?;
+        log::debug!("read the auto-login user {found} back");
+        if found == expected {
+            Ok(())
+        } else {
+            Err(Error::AutoLoginUserMismatch(found))
+        }
+    }
+
+    /// Sets the auto-login user like [`Self::set_auto_login_user`], and returns the previous one it replaced, so it
+    /// can be [restored](Self::restore_auto_login_user).
+    ///
+    /// The previous user is [`None`] if there was none, or if it wasn't a valid username (e.g. it was empty).
+    fn set_auto_login_user_with_backup(&self, username: Username) -> Result<Option<Username>> {
+        let previous = match self.get_auto_login_user() {
+            Ok(previous) => Some(previous),
+            Err(Error::InvalidUsernameInRegistry(_)) => None,
+            Err(Error::ReadSteamRegistry(e)) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        self.set_auto_login_user(username)?;
+        Ok(previous)
+    }
+
+    /// Restores the auto-login user returned by [`Self::set_auto_login_user_with_backup`].
+    ///
+    /// [`None`] [clears](Self::clear_auto_login_user) the auto-login user.
+    fn restore_auto_login_user(&self, previous: Option<Username>) -> Result<()> {
+        match previous {
+            Some(previous) => self.set_auto_login_user(previous),
+            None => self.clear_auto_login_user(),
+        }
+    }
+
+    /// Opens Steam's loginusers.vdf file.
+    fn vdf_loginusers(&self) -> Result<File>;
+
+    /// Launches Steam.
+    fn launch(&self) -> Result<()>;
+
+    /// Launches Steam with the given arguments.
+    fn launch_with_args(&self, args: &[&OsStr]) -> Result<()>;
+
+    /// Launches Steam, skipping Steam's file checks.
+    ///
+    /// By default, [launches](Self::launch_with_args) with `-noverifyfiles`.
+    fn launch_fast(&self) -> Result<()> {
+        self.launch_with_args(&[OsStr::new("-noverifyfiles")])
+    }
+
+    /// Sets whether Steam remembers the password of the account it logs into.
+    ///
+    /// Clearing it makes Steam prompt for the password on its next launch.
+    fn set_remember_password(&self, remember: bool) -> Result<()>;
+
+    /// Gets the account ID (the lower 32 bits of the SteamID64) of the user currently logged into Steam.
+    ///
+    /// Returns [`None`] if no user is logged in.
+    fn get_active_user(&self) -> Result<Option<u32>>;
+
+    /// Kills the Steam client's processes.
+    ///
+    /// Returns how many were killed, which may include helper processes.
+    fn kill(&self) -> Result<usize>;
+
+    /// Kills the Steam client's processes like [`Self::kill`], and also its helper processes that outlive the client,
+    /// such as resident services, if `with_helpers` is set.
+    ///
+    /// By default, the same as [`Self::kill`], for platforms without such helpers.
+    fn kill_with_helpers(&self, with_helpers: bool) -> Result<usize> {
+        let _ = with_helpers;
+        self.kill()
+    }
+
+    /// Kills the Steam client's processes like [`Self::kill`], returning whether any were found and killed.
+    #[inline]
+    fn kill_any(&self) -> Result<bool> {
+        self.kill().map(|killed| killed > 0)
+    }
+
+    /// Asks Steam to shut down gracefully, without waiting for it to exit.
+    fn shutdown(&self) -> Result<()>;
+
+    /// Checks if the Steam client is running.
+    fn is_running(&self) -> Result<bool>;
+
+    /// Gets the process ID of the running Steam client, if any.
+    fn pid(&self) -> Result<Option<u32>>;
+
+    /// Gets the Steam client's version.
+    fn client_version(&self) -> Result<String>;
+
+    /// Checks that the auto-login user's store (the registry on Windows, and the registry file elsewhere) can be read
+    /// and written, without changing it.
+    fn check_registry_access(&self) -> Result<()>;
+
+    /// Waits for the Steam client to exit, e.g. after [`Self::shutdown`], up to the given timeout.
+    ///
+    /// Returns whether it exited in time. By default, polls [`Self::is_running`].
+    fn wait_for_exit(&self, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        while self.is_running()? {
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        Ok(true)
+    }
+
+    /// Checks that none of Steam's processes are alive, including ones that may outlive the client, such as its web
+    /// helpers, though not resident services.
+    ///
+    /// By default, the same as the client not [running](Self::is_running).
+    fn is_fully_stopped(&self) -> Result<bool> {
+        self.is_running().map(|running| !running)
+    }
+}
+
+impl SteamBackend for Steam {
+    #[inline]
+    fn get_auto_login_user(&self) -> Result<Username> {
+        Steam::get_auto_login_user()
+    }
+
+    #[inline]
+    fn get_auto_login_user_raw(&self) -> Result<Vec<u8>> {
+        Steam::get_auto_login_user_raw()
+    }
+
+    #[inline]
+    fn set_auto_login_user(&self, username: Username) -> Result<()> {
+        Steam::set_auto_login_user(username)
+    }
+
+    #[inline]
+    fn clear_auto_login_user(&self) -> Result<()> {
+        Steam::clear_auto_login_user()
+    }
+
+    #[inline]
+    fn vdf_loginusers(&self) -> Result<File> {
+        Steam::vdf_loginusers(self)
+    }
+
+    #[inline]
+    fn launch(&self) -> Result<()> {
+        Steam::launch(self)
+    }
+
+    #[inline]
+    fn launch_with_args(&self, args: &[&OsStr]) -> Result<()> {
+        Steam::launch_with_args(self, args)
+    }
+
+    #[cfg(windows)]
+    #[inline]
+    fn launch_fast(&self) -> Result<()> {
+        Steam::launch_fast(self)
+    }
+
+    #[inline]
+    fn set_remember_password(&self, remember: bool) -> Result<()> {
+        Steam::set_remember_password(remember)
+    }
+
+    #[inline]
+    fn get_active_user(&self) -> Result<Option<u32>> {
+        Steam::get_active_user()
+    }
+
+    #[inline]
+    fn kill(&self) -> Result<usize> {
+        Steam::kill(self)
+    }
+
+    #[inline]
+    fn shutdown(&self) -> Result<()> {
+        Steam::start_shutdown(self)
+    }
+
+    #[inline]
+    fn is_running(&self) -> Result<bool> {
+        Steam::is_running(self)
+    }
+
+    #[cfg(windows)]
+    #[inline]
+    fn kill_with_helpers(&self, with_helpers: bool) -> Result<usize> {
+        Steam::kill_with_helpers(self, with_helpers)
+    }
+
+    #[inline]
+    fn pid(&self) -> Result<Option<u32>> {
+        Steam::pid(self)
+    }
+
+    #[inline]
+    fn client_version(&self) -> Result<String> {
+        Steam::client_version(self)
+    }
+
+    #[inline]
+    fn check_registry_access(&self) -> Result<()> {
+        Steam::check_registry_access()
+    }
+
+    #[cfg(windows)]
+    #[inline]
+    fn wait_for_exit(&self, timeout: Duration) -> Result<bool> {
+        Steam::wait_for_exit(self, timeout)
+    }
+
+    #[cfg(windows)]
+    #[inline]
+    fn is_fully_stopped(&self) -> Result<bool> {
+        Steam::is_fully_stopped(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSteam;
+
+    #[test]
+    fn wait_for_exit_stopped() {
+        let steam = MockSteam::new();
+        let start = Instant::now();
+        assert!(steam.wait_for_exit(Duration::from_secs(10)).unwrap());
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn wait_for_exit_timeout() {
+        let steam = MockSteam::new().with_running(true);
+        let timeout = Duration::from_millis(250);
+        let start = Instant::now();
+        assert!(!steam.wait_for_exit(timeout).unwrap());
+        assert!(start.elapsed() >= timeout);
+        assert!(start.elapsed() < timeout * 4);
+    }
+
+    #[test]
+    fn shutdown_then_wait_for_exit() {
+        let steam = MockSteam::new().with_running(true);
+        steam.shutdown().unwrap();
+        assert!(steam.wait_for_exit(Duration::ZERO).unwrap());
+        assert!(steam.is_fully_stopped().unwrap());
+    }
+
+    #[test]
+    fn ignored_shutdown() {
+        let steam = MockSteam::new()
+            .with_running(true)
+            .with_shutdown_ignored(true);
+        steam.shutdown().unwrap();
+        assert!(!steam.wait_for_exit(Duration::ZERO).unwrap());
+        assert!(!steam.is_fully_stopped().unwrap());
+        assert_eq!(steam.kill().unwrap(), 1);
+        assert!(steam.is_fully_stopped().unwrap());
+    }
+}
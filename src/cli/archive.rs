@@ -0,0 +1,254 @@
+//! Exporting and importing account metadata, for carrying it to another machine.
+
+use std::{collections::BTreeMap, fs, path::Path, process::ExitCode};
+
+use diverter::{Steam, Username};
+
+use super::{
+    config::Config,
+    dry_run::DryRun,
+    list::User,
+    loginusers,
+    output::{error, info, warning},
+};
+
+/// A format to export the account metadata in.
+#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// A JSON document.
+    #[default]
+    Json,
+    /// A TOML document.
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl Format {
+    /// Gets the format of an exported file by its extension, which is JSON unless it's `.toml` and the `toml` feature is enabled.
+    pub fn of_path(path: &Path) -> Self {
+        match path.extension() {
+            #[cfg(feature = "toml")]
+            Some(extension) if extension.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// The exported account metadata.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Archive {
+    /// The auto-login user.
+    #[serde(default)]
+    pub auto_login_user: Option<String>,
+    /// Alias names mapped to the usernames they stand for.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// The users registered with Steam.
+    #[serde(default)]
+    pub users: Vec<User>,
+}
+
+/// Prints the account metadata in the given format to stdout.
+pub fn export(config: &Config, format: Format) -> ExitCode {
+    let auto_login_user = Steam::get_auto_login_user().ok();
+    let users = loginusers::find_steam().and_then(|steam| {
+        loginusers::with_login_users(&steam, |login_users| {
+            login_users
+                .filter_map(|user| {
+                    user.map_err(|e| warning!("skipping a user entry: {e}"))
+                        .ok()
+                })
+                .map(|user| User {
+                    selected: auto_login_user.is_some_and(|selected| selected == *user.username),
+                    ..User::from(user)
+                })
+                .collect()
+        })
+    });
+    let users = users.unwrap_or_else(|e| {
+        warning!("exporting without the registered users: {e}");
+        Vec::new()
+    });
+    let archive = Archive {
+        auto_login_user: auto_login_user.map(|username| username.to_string()),
+        aliases: config.aliases.clone(),
+        users,
+    };
+    let formatted = match format {
+        Format::Json => serde_json::to_string_pretty(&archive)
+            .map(|json| json + "\n")
+            .map_err(|e| e.to_string()),
+        #[cfg(feature = "toml")]
+        Format::Toml => toml::to_string(&archive).map_err(|e| e.to_string()),
+    };
+    match formatted {
+        Ok(formatted) => {
+            print!("{formatted}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("Failed to format the accounts: {e}");
+            ExitCode::from(70)
+        }
+    }
+}
+
+/// Restores the aliases from an exported document, and the auto-login user if `auto_login` is set.
+///
+/// The document's [format](Format::of_path) is told by its extension. Invalid entries are reported and skipped, in which case the exit code is 65.
+pub fn import(path: &Path, auto_login: bool, config: Config, dry_run: DryRun) -> ExitCode {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            error!("Failed to read {}: {e}", path.display());
+            return ExitCode::from(66);
+        }
+    };
+    let archive = match Format::of_path(path) {
+        Format::Json => serde_json::from_str::<Archive>(&source).map_err(|e| e.to_string()),
+        #[cfg(feature = "toml")]
+        Format::Toml => toml::from_str::<Archive>(&source).map_err(|e| e.to_string()),
+    };
+    let archive = match archive {
+        Ok(archive) => archive,
+        Err(e) => {
+            error!("Failed to parse {}: {e}", path.display());
+            return ExitCode::from(65);
+        }
+    };
+
+    let mut failed = match import_aliases(archive.aliases, config, dry_run) {
+        Ok(failed) => failed,
+        Err(code) => return code,
+    };
+
+    if auto_login {
+        match archive.auto_login_user.as_deref().map(Username::try_from) {
+            Some(Ok(username)) => {
+                if dry_run.allows(format_args!("set the auto-login user to {username}")) {
+                    match Steam::set_auto_login_user(username) {
+                        Ok(()) => info!("Set the auto-login user to {username}."),
+                        Err(e) => {
+                            error!("Failed to set the auto-login user: {e}");
+                            return ExitCode::from(&e);
+                        }
+                    }
+                }
+            }
+            Some(Err(e)) => {
+                error!("Skipping the auto-login user: {e}");
+                failed = true;
+            }
+            None => info!("The export has no auto-login user."),
+        }
+    }
+
+    if failed {
+        ExitCode::from(65)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Merges the exported aliases into the configuration file, returning whether any of them were invalid.
+#[cfg(feature = "toml")]
+fn import_aliases(
+    aliases: BTreeMap<String, String>,
+    mut config: Config,
+    dry_run: DryRun,
+) -> Result<bool, ExitCode> {
+    let mut failed = false;
+    let mut imported = 0;
+    for (alias, username) in aliases {
+        match Username::try_from(username.as_str()) {
+            Ok(username) => {
+                let username = username.to_string();
+                if config.aliases.get(&alias) != Some(&username) {
+                    config.aliases.insert(alias, username);
+                    imported += 1;
+                }
+            }
+            Err(e) => {
+                error!("Skipping the alias {alias}: invalid username {username}: {e}");
+                failed = true;
+            }
+        }
+    }
+    if imported > 0 && dry_run.allows(format_args!("save {imported} imported aliases")) {
+        match config.save() {
+            Ok(()) => info!("Imported {imported} aliases."),
+            Err(e) => {
+                error!("Failed to save the aliases: {e}");
+                return Err(ExitCode::from(73));
+            }
+        }
+    }
+    Ok(failed)
+}
+
+/// Skips the exported aliases, as there's no configuration file to merge them into without the `toml` feature.
+#[cfg(not(feature = "toml"))]
+fn import_aliases(
+    aliases: BTreeMap<String, String>,
+    _config: Config,
+    _dry_run: DryRun,
+) -> Result<bool, ExitCode> {
+    if !aliases.is_empty() {
+        warning!("skipping the aliases, this build has no configuration file to save them to (see the toml feature).");
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use diverter::vdf::OwnedLoginUser;
+
+    use super::*;
+
+    fn archive() -> Archive {
+        let user = |steamid: &str, username: &str, selected| User {
+            login_user: OwnedLoginUser {
+                steamid: steamid.to_owned(),
+                username: username.to_owned(),
+                nickname: username.to_uppercase(),
+                allow_auto_login: true,
+                remember_password: true,
+                most_recent: selected,
+                timestamp: Some(1_700_000_000),
+            },
+            selected,
+        };
+        Archive {
+            auto_login_user: Some("alice".to_owned()),
+            aliases: BTreeMap::from([("main".to_owned(), "alice".to_owned())]),
+            users: vec![
+                user("76561197960287930", "alice", true),
+                user("76561197960287931", "bob", false),
+            ],
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn format_of_path() {
+        assert_eq!(Format::of_path(Path::new("accounts.json")), Format::Json);
+        assert_eq!(Format::of_path(Path::new("accounts.toml")), Format::Toml);
+        assert_eq!(Format::of_path(Path::new("accounts.TOML")), Format::Toml);
+        assert_eq!(Format::of_path(Path::new("accounts")), Format::Json);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let archive = archive();
+        let json = serde_json::to_string_pretty(&archive).unwrap();
+        assert_eq!(serde_json::from_str::<Archive>(&json).unwrap(), archive);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_round_trip() {
+        let archive = archive();
+        let toml = toml::to_string(&archive).unwrap();
+        assert_eq!(toml::from_str::<Archive>(&toml).unwrap(), archive);
+    }
+}
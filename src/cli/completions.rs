@@ -0,0 +1,91 @@
+//! Shell completion, with usernames completed dynamically.
+//!
+//! The scripts call back into diverter to complete, through clap_complete's [`CompleteEnv`], which `main` runs
+//! first thing.
+
+use std::io::{self, Write};
+
+use clap_complete::{env::Shells, CompleteEnv, CompletionCandidate, Shell};
+use diverter::Username;
+
+use super::loginusers;
+
+/// The environment variable that asks diverter to complete rather than run.
+pub const VAR: &str = "COMPLETE";
+
+/// Completes the command line instead of running, and exits, if the shell asked for it via the [variable](VAR).
+pub fn complete(command: fn() -> clap::Command) {
+    CompleteEnv::with_factory(command).var(VAR).complete();
+}
+
+/// Gets the registered usernames as completion candidates, described by their nicknames.
+///
+/// Gets nothing if they can't be read, since a completion script has no good way to report errors. Only reads
+/// loginusers.vdf, so it's quick and doesn't depend on whether Steam is running.
+pub fn usernames() -> Vec<CompletionCandidate> {
+    let usernames = loginusers::find_steam().and_then(|steam| {
+        loginusers::with_login_users(&steam, |login_users| {
+            login_users
+                .filter_map(Result::ok)
+                .filter_map(|user| {
+                    let username = Username::try_from(user.username).ok()?;
+                    let nickname = String::from_utf8_lossy(user.nickname).into_owned();
+                    Some(CompletionCandidate::new(username.to_string()).help(Some(nickname.into())))
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    usernames.unwrap_or_default()
+}
+
+/// Writes the script that registers the given command's completion with the given shell.
+///
+/// The script runs the command by its name, so it must be on the `PATH`.
+pub fn generate(shell: Shell, command: &clap::Command, out: &mut impl Write) -> io::Result<()> {
+    let name = command.get_name();
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| io::Error::other(format!("unsupported shell {shell}")))?;
+    completer.write_registration(VAR, name, name, name, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, ValueEnum};
+    use clap_complete::ArgValueCandidates;
+
+    use super::*;
+
+    #[test]
+    fn generates_for_every_shell() {
+        for &shell in Shell::value_variants() {
+            let mut script = Vec::new();
+            generate(shell, &crate::Cli::command(), &mut script).unwrap();
+            assert!(!script.is_empty(), "empty {shell} completion script");
+        }
+    }
+
+    #[test]
+    fn usernames_complete_dynamically() {
+        let command = crate::Cli::command();
+        for path in [
+            &["set"][..],
+            &["rename"],
+            #[cfg(feature = "toml")]
+            &["alias", "add"],
+        ] {
+            let subcommand = path.iter().fold(&command, |command, name| {
+                command.find_subcommand(name).unwrap()
+            });
+            let username = subcommand
+                .get_arguments()
+                .find(|arg| arg.get_id() == "username")
+                .unwrap();
+            assert!(
+                username.get::<ArgValueCandidates>().is_some(),
+                "{path:?} doesn't complete usernames"
+            );
+        }
+    }
+}
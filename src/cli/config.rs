@@ -0,0 +1,175 @@
+//! The configuration file.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "toml")]
+use std::{fs, io, path::PathBuf};
+
+use diverter::{Username, UsernameError};
+
+/// The configuration file contents.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Config {
+    /// Whether to restart Steam after switching by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<bool>,
+    /// Whether to restart Steam gracefully by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graceful: Option<bool>,
+    /// Whether to let Steam verify its files after a restart by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<bool>,
+    /// Whether to start Steam to the system tray after a restart by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub silent: Option<bool>,
+    /// When to print with color by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorMode>,
+    /// Alias names mapped to the usernames they stand for.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// Global hotkeys, such as `Ctrl+Alt+1`, mapped to the usernames or aliases they switch to in the daemon.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hotkeys: BTreeMap<String, String>,
+}
+
+/// When to print with color.
+#[derive(
+    Debug,
+    Default,
+    Hash,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Print with color when printing to a terminal, unless the `NO_COLOR` environment variable is set.
+    #[default]
+    Auto,
+    /// Always print with color.
+    Always,
+    /// Never print with color.
+    Never,
+}
+
+impl ColorMode {
+    /// Gets whether to color, given whether `NO_COLOR` is set and whether the stream is a terminal.
+    ///
+    /// An explicit mode takes precedence over `NO_COLOR`.
+    pub const fn resolve(self, no_color: bool, is_terminal: bool) -> bool {
+        match self {
+            Self::Auto => !no_color && is_terminal,
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+/// A configuration file error.
+#[cfg(feature = "toml")]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The configuration directory couldn't be determined.
+    #[cfg_attr(
+        windows,
+        error("couldn't locate the configuration directory, %APPDATA% is not set")
+    )]
+    #[cfg_attr(
+        not(windows),
+        error("couldn't locate the configuration directory, $HOME is not set")
+    )]
+    NoConfigDir,
+    /// Failed to read or write the configuration file.
+    #[error("failed to access the configuration file {}: {1}", .0.display())]
+    Io(PathBuf, io::Error),
+    /// The configuration file is malformed.
+    #[error("failed to parse the configuration file {}: {1}", .0.display())]
+    Parse(PathBuf, toml::de::Error),
+    /// The configuration couldn't be serialized.
+    #[error("failed to serialize the configuration: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Gets the platform's directory for per-user configuration files.
+///
+/// That's `%APPDATA%` on Windows.
+#[cfg(all(feature = "toml", windows))]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+}
+
+/// Gets the platform's directory for per-user configuration files.
+///
+/// That's `~/Library/Application Support` on macOS.
+#[cfg(all(feature = "toml", target_os = "macos"))]
+fn config_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(
+        PathBuf::from(home)
+            .join("Library")
+            .join("Application Support"),
+    )
+}
+
+/// Gets the platform's directory for per-user configuration files.
+///
+/// That's `$XDG_CONFIG_HOME`, or `~/.config` if it's unset or not absolute, per the XDG Base Directory
+/// Specification.
+#[cfg(all(feature = "toml", not(any(windows, target_os = "macos"))))]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| Some(PathBuf::from(std::env::var_os("HOME")?).join(".config")))
+}
+
+#[cfg(feature = "toml")]
+impl Config {
+    /// Gets the path to the configuration file (`diverter/config.toml` in the [configuration directory](config_dir)).
+    pub fn path() -> Result<PathBuf, Error> {
+        let dir = config_dir().ok_or(Error::NoConfigDir)?;
+        Ok(dir.join("diverter").join("config.toml"))
+    }
+
+    /// Loads the configuration file, or the default configuration if there's no configuration file.
+    pub fn load() -> Result<Self, Error> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(source) => toml::from_str(&source).map_err(|e| Error::Parse(path, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::Io(path, e)),
+        }
+    }
+
+    /// Saves the configuration file.
+    pub fn save(&self) -> Result<(), Error> {
+        let path = Self::path()?;
+        let source = toml::to_string(self)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| Error::Io(path.clone(), e))?;
+        }
+        fs::write(&path, source).map_err(|e| Error::Io(path, e))
+    }
+}
+
+impl Config {
+    /// Resolves the given account name, which may either be an alias or a username, to a [`Username`].
+    ///
+    /// Surrounding whitespace is ignored.
+    pub fn resolve(&self, name: &str) -> Result<Username, UsernameError> {
+        let name = name.trim_matches(|c: char| c.is_ascii_whitespace());
+        Username::try_from_trimmed(self.aliases.get(name).map_or(name, String::as_str))
+    }
+
+    /// Gets the aliases of the given username.
+    pub fn aliases_of<'a>(&'a self, username: &'a [u8]) -> impl Iterator<Item = &'a str> + 'a {
+        self.aliases
+            .iter()
+            .filter(move |(_, target)| target.as_bytes().eq_ignore_ascii_case(username))
+            .map(|(alias, _)| alias.as_str())
+    }
+}
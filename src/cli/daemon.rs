@@ -0,0 +1,169 @@
+//! Switching accounts with global hotkeys.
+
+use std::{
+    collections::BTreeMap,
+    io, mem, ptr,
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
+
+use diverter::Username;
+use winapi::{
+    shared::minwindef::{BOOL, DWORD, FALSE, TRUE, UINT},
+    um::{
+        consoleapi::SetConsoleCtrlHandler,
+        processthreadsapi::GetCurrentThreadId,
+        wincon::{CTRL_BREAK_EVENT, CTRL_C_EVENT},
+        winuser::{
+            GetMessageW, PostThreadMessageW, RegisterHotKey, UnregisterHotKey, MOD_ALT,
+            MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, MSG, WM_HOTKEY, WM_QUIT,
+        },
+    },
+};
+
+use super::output::{error, info};
+
+/// The ID of the thread running the message loop, to stop it on Ctrl+C.
+static LOOP_THREAD: AtomicU32 = AtomicU32::new(0);
+
+/// Stops the message loop on Ctrl+C or Ctrl+Break.
+unsafe extern "system" fn on_ctrl(ctrl_type: DWORD) -> BOOL {
+    if ctrl_type == CTRL_C_EVENT || ctrl_type == CTRL_BREAK_EVENT {
+        PostThreadMessageW(LOOP_THREAD.load(Ordering::Relaxed), WM_QUIT, 0, 0);
+        TRUE
+    } else {
+        FALSE
+    }
+}
+
+/// How long after a switch to ignore further hotkeys, so hotkeys pressed while switching don't trigger more.
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// A hotkey parse error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HotkeyError {
+    /// The hotkey has no key besides modifiers.
+    #[error("the hotkey {0} has no key, only modifiers")]
+    NoKey(String),
+    /// The hotkey has more than one key besides modifiers.
+    #[error("the hotkey {0} has more than one key besides modifiers")]
+    MultipleKeys(String),
+    /// The hotkey has a key that isn't supported.
+    #[error("unsupported key {1} in the hotkey {0}, expected a letter, a digit or F1-F24")]
+    UnsupportedKey(String, String),
+}
+
+/// A global hotkey.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    /// The modifiers, as `MOD_*` flags.
+    modifiers: UINT,
+    /// The virtual-key code.
+    key: UINT,
+}
+
+impl Hotkey {
+    /// Parses a hotkey such as `Ctrl+Alt+1`, case-insensitively.
+    ///
+    /// The modifiers are Ctrl (or Control), Alt, Shift and Win, and the key is a letter, a digit or F1-F24.
+    pub fn parse(text: &str) -> Result<Self, HotkeyError> {
+        let mut modifiers = 0;
+        let mut key = None;
+        for part in text.split('+').map(str::trim) {
+            let modifier = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => MOD_CONTROL,
+                "alt" => MOD_ALT,
+                "shift" => MOD_SHIFT,
+                "win" => MOD_WIN,
+                _ => {
+                    if key.is_some() {
+                        return Err(HotkeyError::MultipleKeys(text.to_owned()));
+                    }
+                    key = Some(virtual_key(part).ok_or_else(|| {
+                        HotkeyError::UnsupportedKey(text.to_owned(), part.to_owned())
+                    })?);
+                    continue;
+                }
+            };
+            modifiers |= modifier as UINT;
+        }
+        let key = key.ok_or_else(|| HotkeyError::NoKey(text.to_owned()))?;
+        Ok(Self { modifiers, key })
+    }
+}
+
+/// Gets the virtual-key code of a letter, a digit or a function key (F1-F24).
+fn virtual_key(key: &str) -> Option<UINT> {
+    match key.as_bytes() {
+        // letters' and digits' virtual-key codes are their uppercase ASCII codes.
+        &[c] if c.is_ascii_alphanumeric() => Some(c.to_ascii_uppercase() as UINT),
+        [b'F' | b'f', number @ ..] => {
+            let number = std::str::from_utf8(number).ok()?.parse::<UINT>().ok()?;
+            // VK_F1 is 0x70.
+            (1..=24).contains(&number).then(|| 0x70 + number - 1)
+        }
+        _ => None,
+    }
+}
+
+/// Registers the hotkeys, mapped to the usernames to switch to, and calls `switch` whenever one is pressed, until
+/// interrupted with Ctrl+C.
+///
+/// Hotkeys that fail to register (e.g. because another program took them) are reported and skipped.
+pub fn run(
+    hotkeys: &BTreeMap<String, (Hotkey, Username)>,
+    mut switch: impl FnMut(Username),
+) -> io::Result<()> {
+    LOOP_THREAD.store(unsafe { GetCurrentThreadId() }, Ordering::Relaxed);
+    // SAFETY: the handler only posts a message.
+    unsafe { SetConsoleCtrlHandler(Some(on_ctrl), TRUE) };
+
+    // the ID of each hotkey is its index.
+    let mut registered = Vec::with_capacity(hotkeys.len());
+    for (id, (text, (hotkey, username))) in hotkeys.iter().enumerate() {
+        let modifiers = hotkey.modifiers | MOD_NOREPEAT as UINT;
+        // SAFETY: no preconditions.
+        if unsafe { RegisterHotKey(ptr::null_mut(), id as _, modifiers, hotkey.key) } == FALSE {
+            error!(
+                "Failed to register the hotkey {text}: {}",
+                io::Error::last_os_error()
+            );
+            continue;
+        }
+        info!("⌨️ {text} switches to {username}");
+        registered.push(id);
+    }
+    if registered.is_empty() {
+        return Err(io::Error::other("no hotkey could be registered"));
+    }
+    info!("👀 waiting for hotkeys, press Ctrl+C to stop..");
+
+    let targets = hotkeys
+        .values()
+        .map(|&(_, username)| username)
+        .collect::<Vec<_>>();
+    let mut last_switch: Option<Instant> = None;
+    // SAFETY: MSG is plain data.
+    let mut message: MSG = unsafe { mem::zeroed() };
+    let result = loop {
+        // SAFETY: the message is valid for writes.
+        match unsafe { GetMessageW(&mut message, ptr::null_mut(), 0, 0) } {
+            0 => break Ok(()),
+            -1 => break Err(io::Error::last_os_error()),
+            _ if message.message != WM_HOTKEY => {}
+            _ if last_switch.is_some_and(|last| last.elapsed() < DEBOUNCE) => {}
+            _ => {
+                if let Some(&username) = targets.get(message.wParam) {
+                    switch(username);
+                    last_switch = Some(Instant::now());
+                }
+            }
+        }
+    };
+
+    for id in registered {
+        // SAFETY: the hotkey was registered by this thread.
+        unsafe { UnregisterHotKey(ptr::null_mut(), id as _) };
+    }
+    result
+}
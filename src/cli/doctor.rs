@@ -0,0 +1,199 @@
+//! Diagnosing the environment diverter runs in.
+
+use std::{borrow::Cow, fmt::Display, io, process::ExitCode};
+
+use diverter::{
+    vdf::{self, LoginUser},
+    Steam,
+};
+
+use super::loginusers;
+
+/// The outcome of a check.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// The check passed.
+    Pass,
+    /// The check passed, but something may be off.
+    Warn,
+    /// The check failed.
+    Fail,
+}
+
+/// Collects and prints the outcomes of the checks.
+#[derive(Debug, Default)]
+struct Report {
+    failed: bool,
+}
+
+impl Report {
+    /// Prints the outcome of a check.
+    fn check(&mut self, outcome: Outcome, name: &str, details: impl Display) {
+        let label = match outcome {
+            Outcome::Pass => "PASS",
+            Outcome::Warn => "WARN",
+            Outcome::Fail => "FAIL",
+        };
+        self.failed |= outcome == Outcome::Fail;
+        println!("[{label}] {name}: {details}");
+    }
+}
+
+/// Gets the 1-based line and column of the given byte offset in the source.
+fn line_column(source: &[u8], offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+    let column = before.len()
+        - before
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |i| i + 1)
+        + 1;
+    (line, column)
+}
+
+/// Scans and parses the source text, describing the position of the error on failure.
+fn parse_at(source: &[u8]) -> Result<vdf::Document<'_>, String> {
+    loginusers::parse_with_offset(source).map_err(|(offset, e)| {
+        let (line, column) = line_column(source, offset);
+        match e {
+            vdf::ScanParseError::ScanError(e) => {
+                format!("lexing error at line {line}, column {column}: {e}")
+            }
+            vdf::ScanParseError::ParseError(e) => {
+                format!("parsing error at line {line}, column {column}: {e}")
+            }
+        }
+    })
+}
+
+/// Checks loginusers.vdf can be read and parsed, returning the usernames registered in it.
+fn check_login_users(report: &mut Report) -> Option<Vec<Vec<u8>>> {
+    const NAME: &str = "loginusers.vdf";
+    let source = loginusers::find_steam()
+        .and_then(|steam| loginusers::read(&steam))
+        .map_err(|e| report.check(Outcome::Fail, NAME, e))
+        .ok()?;
+    let document = parse_at(&source)
+        .map_err(|e| report.check(Outcome::Fail, NAME, e))
+        .ok()?;
+    let users = LoginUser::from_vdf(&document)
+        .map_err(|e| report.check(Outcome::Fail, NAME, e))
+        .ok()?;
+
+    let mut usernames = Vec::new();
+    let mut errors = Vec::new();
+    for user in users {
+        match user {
+            Ok(user) => usernames.push(user.username.to_vec()),
+            Err(e) => errors.push(e),
+        }
+    }
+    match errors.first() {
+        None => report.check(
+            Outcome::Pass,
+            NAME,
+            format_args!("{} registered users", usernames.len()),
+        ),
+        Some(e) => report.check(
+            Outcome::Warn,
+            NAME,
+            format_args!(
+                "{} registered users, {} unreadable entries ({e})",
+                usernames.len(),
+                errors.len()
+            ),
+        ),
+    }
+    Some(usernames)
+}
+
+/// Checks Steam's registry subkey, installation, logged in users data, auto-login user and process, printing the
+/// outcome of each check.
+///
+/// Exits with code 69 if any check failed.
+pub fn doctor() -> ExitCode {
+    let mut report = Report::default();
+
+    match Steam::check_registry_access() {
+        Ok(()) => report.check(
+            Outcome::Pass,
+            "registry",
+            "Steam's subkey is readable and writable",
+        ),
+        Err(e) => report.check(Outcome::Fail, "registry", e),
+    }
+
+    let steam = match super::steam::find() {
+        Ok(steam) => steam,
+        Err(e) => {
+            report.check(Outcome::Fail, "client", e);
+            return ExitCode::from(69);
+        }
+    };
+    let client = steam.client_path();
+    let label = client
+        .file_name()
+        .map_or(Cow::Borrowed("client"), |name| name.to_string_lossy());
+    if client.is_file() {
+        match steam.client_version() {
+            Ok(version) => report.check(
+                Outcome::Pass,
+                &label,
+                format_args!("{} (version {version})", client.display()),
+            ),
+            Err(e) => report.check(
+                Outcome::Warn,
+                &label,
+                format_args!("{} ({e})", client.display()),
+            ),
+        }
+    } else {
+        report.check(
+            Outcome::Fail,
+            &label,
+            format_args!("{} isn't a file", client.display()),
+        );
+    }
+
+    let usernames = check_login_users(&mut report);
+
+    match Steam::get_auto_login_user() {
+        Ok(username) => {
+            let registered = usernames.as_ref().map(|usernames| {
+                usernames
+                    .iter()
+                    .any(|registered| registered.eq_ignore_ascii_case(username.as_bytes()))
+            });
+            if registered == Some(false) {
+                report.check(
+                    Outcome::Warn,
+                    "auto-login user",
+                    format_args!("{username} isn't registered in loginusers.vdf"),
+                );
+            } else {
+                report.check(Outcome::Pass, "auto-login user", username);
+            }
+        }
+        Err(diverter::Error::ReadSteamRegistry(e)) if e.kind() == io::ErrorKind::NotFound => {
+            report.check(Outcome::Warn, "auto-login user", "none is set")
+        }
+        Err(e) => report.check(Outcome::Fail, "auto-login user", e),
+    }
+
+    match steam.pid() {
+        Ok(Some(pid)) => report.check(
+            Outcome::Pass,
+            "process",
+            format_args!("running (PID {pid})"),
+        ),
+        Ok(None) => report.check(Outcome::Pass, "process", "not running"),
+        Err(e) => report.check(Outcome::Fail, "process", e),
+    }
+
+    if report.failed {
+        ExitCode::from(69)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
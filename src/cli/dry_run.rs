@@ -0,0 +1,22 @@
+//! Dry-run mode.
+
+use std::fmt::Display;
+
+use super::output::error;
+
+/// Whether to only report mutations instead of performing them.
+#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq)]
+pub struct DryRun(pub bool);
+
+impl DryRun {
+    /// Checks whether to perform the described mutation.
+    ///
+    /// In dry-run mode, reports the mutation to stderr and returns false.
+    /// The report is only silenced along with errors, since it's the point of a dry run.
+    pub fn allows(self, action: impl Display) -> bool {
+        if self.0 {
+            error!("Would {action}.");
+        }
+        !self.0
+    }
+}
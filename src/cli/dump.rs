@@ -0,0 +1,25 @@
+//! Debug dumps of VDF processing stages.
+
+use std::io::{self, Write};
+
+use diverter::vdf::{ScanError, Scanner};
+
+/// Writes the tokens of the given VDF source, one per line, as their offset, type and lexeme.
+///
+/// Stops at the first scanning error, which is returned along with its offset.
+pub fn tokens(source: &[u8], out: &mut impl Write) -> io::Result<Result<(), (usize, ScanError)>> {
+    let mut scanner = Scanner::new(source);
+    while let Some(token) = scanner.next() {
+        match token {
+            Ok(token) => writeln!(
+                out,
+                "{}\t{:?}\t{}",
+                scanner.start,
+                token.r#type,
+                token.lexeme.escape_ascii()
+            )?,
+            Err(e) => return Ok(Err((scanner.start, e))),
+        }
+    }
+    Ok(Ok(()))
+}
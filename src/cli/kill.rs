@@ -0,0 +1,73 @@
+//! Stopping Steam.
+
+use std::{process::ExitCode, time::Duration};
+
+use diverter::SteamBackend;
+
+use super::output::{error, info};
+
+/// The exit code for when Steam didn't shut down gracefully in time.
+pub const EXIT_STILL_RUNNING: u8 = 75;
+
+/// Stops Steam, reporting the result to stderr.
+///
+/// Shuts Steam down gracefully within the given timeout if any, and then kills it if it's still running and
+/// `then_kill` is set. Otherwise, kills Steam outright, including its helper processes if `with_helpers` is set.
+pub fn kill(graceful: Option<Duration>, then_kill: bool, with_helpers: bool) -> ExitCode {
+    let steam = match super::steam::find() {
+        Ok(steam) => steam,
+        Err(e) => {
+            error!("Failed to find Steam: {e}");
+            return ExitCode::from(&e);
+        }
+    };
+
+    if let Some(timeout) = graceful {
+        match steam.is_running() {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("Steam isn't running.");
+                return ExitCode::SUCCESS;
+            }
+            Err(e) => {
+                error!("Failed to check whether Steam is running: {e}");
+                return ExitCode::from(&e);
+            }
+        }
+        match steam
+            .start_shutdown()
+            .and_then(|()| SteamBackend::wait_for_exit(&steam, timeout))
+        {
+            Ok(true) => {
+                info!("🛑 Steam has been shut down");
+                return ExitCode::SUCCESS;
+            }
+            Ok(false) if then_kill => info!(
+                "Steam didn't shut down within {}s, killing it..",
+                timeout.as_secs()
+            ),
+            Ok(false) => {
+                error!(
+                    "Steam didn't shut down within {}s, it's still running.",
+                    timeout.as_secs()
+                );
+                return ExitCode::from(EXIT_STILL_RUNNING);
+            }
+            Err(e) => {
+                error!("Failed to shut down Steam: {e}");
+                return ExitCode::from(&e);
+            }
+        }
+    }
+
+    match SteamBackend::kill_with_helpers(&steam, with_helpers) {
+        Ok(0) => info!("Steam isn't running."),
+        Ok(1) => info!("🔪 Steam has been killed"),
+        Ok(killed) => info!("🔪 Steam has been killed ({killed} processes)"),
+        Err(e) => {
+            error!("Failed to kill Steam: {e}");
+            return ExitCode::from(&e);
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,63 @@
+//! Launching Steam.
+
+use std::{
+    ffi::{OsStr, OsString},
+    process::ExitCode,
+};
+
+use diverter::SteamBackend;
+
+use super::output::{error, info};
+
+/// The exit code for when Steam is already running.
+pub const EXIT_ALREADY_RUNNING: u8 = 75;
+
+/// Launches Steam with the given arguments, reporting the result to stderr.
+///
+/// Refuses to launch Steam if it's already running, unless forced.
+pub fn launch(verify: bool, force: bool, args: &[OsString]) -> ExitCode {
+    let steam = match super::steam::find() {
+        Ok(steam) => steam,
+        Err(e) => {
+            error!("Failed to find Steam: {e}");
+            return ExitCode::from(&e);
+        }
+    };
+
+    if !force {
+        match steam.is_running() {
+            Ok(false) => {}
+            Ok(true) => {
+                error!("Steam is already running, use --force to launch it anyway.");
+                return ExitCode::from(EXIT_ALREADY_RUNNING);
+            }
+            Err(e) => {
+                error!("Failed to check whether Steam is running: {e}");
+                return ExitCode::from(&e);
+            }
+        }
+    }
+
+    let result = match (verify, args.is_empty()) {
+        (true, true) => steam.launch(),
+        (false, true) => SteamBackend::launch_fast(&steam),
+        (verify, false) => {
+            let mut launch_args = Vec::with_capacity(args.len() + 1);
+            if !verify {
+                launch_args.push(OsStr::new("-noverifyfiles"));
+            }
+            launch_args.extend(args.iter().map(OsString::as_os_str));
+            steam.launch_with_args(&launch_args)
+        }
+    };
+    match result {
+        Ok(()) => {
+            info!("🚀 launched Steam");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("Failed to launch Steam: {e}");
+            ExitCode::from(&e)
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Listing the [login users](LoginUser) in machine-readable formats.
+
+use diverter::vdf::{LoginUser, OwnedLoginUser};
+
+/// A format to list the login users in.
+#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// A TOML document with a `users` array of tables.
+    #[cfg(feature = "toml")]
+    Toml,
+    /// A JSON object with a `users` array, and an `errors` array of the entries that couldn't be read.
+    Json,
+}
+
+/// An exported login user.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct User {
+    /// The user's login record.
+    ///
+    /// TOML has no null, so the timestamp is left out there when unknown.
+    #[serde(flatten)]
+    pub login_user: OwnedLoginUser,
+    /// Whether the user is the auto-login user.
+    #[serde(default)]
+    pub selected: bool,
+}
+
+impl<'a> From<LoginUser<'a>> for User {
+    fn from(user: LoginUser<'a>) -> Self {
+        Self {
+            login_user: user.into(),
+            selected: false,
+        }
+    }
+}
+
+/// The exported users document.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Users {
+    /// The users.
+    pub users: Vec<User>,
+    /// The errors reading user entries.
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[cfg(feature = "toml")]
+impl Users {
+    /// Serializes the users to a TOML document, without the errors.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        /// The TOML document, which leaves the errors out.
+        #[derive(serde::Serialize)]
+        struct Document<'a> {
+            users: &'a [User],
+        }
+
+        toml::to_string(&Document { users: &self.users })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users() -> Users {
+        Users {
+            users: vec![
+                User {
+                    login_user: OwnedLoginUser {
+                        steamid: "76561197960287930".to_owned(),
+                        username: "alice".to_owned(),
+                        nickname: "Alice".to_owned(),
+                        allow_auto_login: true,
+                        remember_password: true,
+                        most_recent: true,
+                        timestamp: Some(1_700_000_000),
+                    },
+                    selected: true,
+                },
+                User {
+                    login_user: OwnedLoginUser {
+                        steamid: "76561197960287931".to_owned(),
+                        username: "bob".to_owned(),
+                        nickname: "Bob".to_owned(),
+                        allow_auto_login: false,
+                        remember_password: false,
+                        most_recent: false,
+                        timestamp: None,
+                    },
+                    selected: false,
+                },
+            ],
+            errors: Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_structure() {
+        let toml = users().to_toml().unwrap();
+        assert_eq!(
+            toml,
+            r#"[[users]]
+steamid = "76561197960287930"
+username = "alice"
+nickname = "Alice"
+allow_auto_login = true
+remember_password = true
+most_recent = true
+timestamp = 1700000000
+selected = true
+
+[[users]]
+steamid = "76561197960287931"
+username = "bob"
+nickname = "Bob"
+allow_auto_login = false
+remember_password = false
+most_recent = false
+selected = false
+"#
+        );
+    }
+
+    #[test]
+    fn json_structure() {
+        let mut users = users();
+        users
+            .errors
+            .push("missing expected \"AccountName\" key".to_owned());
+        let json = serde_json::to_value(&users).unwrap();
+        assert_eq!(json["users"][0]["username"], "alice");
+        assert_eq!(json["users"][0]["timestamp"], 1_700_000_000);
+        assert_eq!(json["users"][1]["timestamp"], serde_json::Value::Null);
+        assert_eq!(json["users"][1]["selected"], false);
+        assert_eq!(json["errors"][0], "missing expected \"AccountName\" key");
+        assert_eq!(serde_json::from_value::<Users>(json).unwrap(), users);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_round_trip() {
+        let users = users();
+        let toml = users.to_toml().unwrap();
+        assert_eq!(toml::from_str::<Users>(&toml).unwrap(), users);
+    }
+}
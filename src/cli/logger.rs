@@ -0,0 +1,48 @@
+//! Diagnostic logging to stderr.
+
+use std::time::Instant;
+
+use log::{Level, Log, Metadata, Record};
+
+/// Logs records to stderr, prefixed by the time elapsed since the logger was installed.
+struct Logger {
+    start: Instant,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let elapsed = self.start.elapsed();
+            eprintln!(
+                "[{:>4}.{:03}s {:<5}] {}",
+                elapsed.as_secs(),
+                elapsed.subsec_millis(),
+                record.level(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logger, logging debug records if `verbosity` is 1, and trace records too if it's more.
+///
+/// Does nothing if `verbosity` is 0.
+pub fn init(verbosity: u8) {
+    let level = match verbosity {
+        0 => return,
+        1 => Level::Debug,
+        _ => Level::Trace,
+    };
+    let logger = Box::new(Logger {
+        start: Instant::now(),
+    });
+    if log::set_boxed_logger(logger).is_ok() {
+        log::set_max_level(level.to_level_filter());
+    }
+}
@@ -0,0 +1,184 @@
+//! Access to Steam's loginusers.vdf.
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use diverter::{
+    vdf::{self, Scanner},
+    Steam, SteamBackend, SteamId, Username, UsernameError,
+};
+
+/// Failure to load the [login users](vdf::LoginUser).
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Failed to find Steam.
+    #[error("failed to find Steam: {0}")]
+    FindSteam(diverter::Error),
+    /// Failed to open loginusers.vdf.
+    #[error("failed to find logged in users data: {0}")]
+    Open(diverter::Error),
+    /// Failed to read loginusers.vdf.
+    #[error("failed to read logged in users data: {0}")]
+    Read(io::Error),
+    /// Failed to parse loginusers.vdf.
+    #[error("failed to parse logged in users data: {0}")]
+    Parse(#[from] vdf::ScanParseError),
+    /// Failed to read a given loginusers.vdf-format file.
+    #[error("failed to read {}: {1}", .0.display())]
+    ReadFile(PathBuf, io::Error),
+    /// Failed to parse a given loginusers.vdf-format file, at the given byte offset.
+    #[error("failed to parse {} at byte {offset}: {error}", path.display())]
+    ParseFile {
+        path: PathBuf,
+        offset: usize,
+        error: vdf::ScanParseError,
+    },
+    /// Failed to find the users in loginusers.vdf.
+    #[error("failed to parse logged in users data: {0}")]
+    Users(#[from] vdf::LoginUserVdfError),
+    /// The user marked as the most recent has an invalid username.
+    #[error("the most recent account's username is invalid: {0}")]
+    InvalidMostRecent(UsernameError),
+    /// Several users are marked as the most recent, holding their usernames.
+    #[error("several accounts are marked as the most recent in the logged in users data: {}", .0.join(", "))]
+    AmbiguousMostRecent(Vec<String>),
+}
+
+/// Exit codes per `sysexits.h`.
+impl<'a> From<&'a Error> for ExitCode {
+    fn from(e: &'a Error) -> Self {
+        match e {
+            Error::FindSteam(e) | Error::Open(e) => ExitCode::from(e),
+            Error::ReadFile(..) => ExitCode::from(66),
+            Error::ParseFile { .. }
+            | Error::InvalidMostRecent(_)
+            | Error::AmbiguousMostRecent(_) => ExitCode::from(65),
+            _ => ExitCode::from(69),
+        }
+    }
+}
+
+/// Gets the path to loginusers.vdf of the given Steam installation.
+pub fn path(steam: &Steam) -> PathBuf {
+    steam.path().join("config").join("loginusers.vdf")
+}
+
+/// Finds Steam per [`super::steam::find`], for reading its loginusers.vdf.
+pub fn find_steam() -> Result<Steam, Error> {
+    super::steam::find().map_err(Error::FindSteam)
+}
+
+/// Reads the loginusers.vdf source of the given Steam.
+///
+/// The source is read as is rather than as UTF-8, so text such as nicknames can be printed verbatim.
+pub fn read(steam: &dyn SteamBackend) -> Result<Vec<u8>, Error> {
+    let mut vdf_file = steam.vdf_loginusers().map_err(Error::Open)?;
+    let mut vdf_source = Vec::with_capacity(4096);
+    vdf_file.read_to_end(&mut vdf_source).map_err(Error::Read)?;
+    Ok(vdf_source)
+}
+
+/// Reads and parses the [login users](vdf::LoginUser) out of the given Steam's loginusers.vdf and passes them to `f`.
+pub fn with_login_users<R>(
+    steam: &dyn SteamBackend,
+    f: impl FnOnce(&mut dyn Iterator<Item = Result<vdf::LoginUser, vdf::LoginUserVdfError>>) -> R,
+) -> Result<R, Error> {
+    let vdf_source = read(steam)?;
+    let document = vdf::scan_parse(&vdf_source)?;
+    let mut login_users = vdf::LoginUser::from_vdf(&document)?;
+    Ok(f(&mut login_users))
+}
+
+/// Reads and parses the [login users](vdf::LoginUser) out of the loginusers.vdf-format file at the given path, e.g. a
+/// backup from another machine, and passes them to `f`.
+pub fn with_login_users_in<R>(
+    path: &Path,
+    f: impl FnOnce(&mut dyn Iterator<Item = Result<vdf::LoginUser, vdf::LoginUserVdfError>>) -> R,
+) -> Result<R, Error> {
+    let source = fs::read(path).map_err(|e| Error::ReadFile(path.to_owned(), e))?;
+    let document = parse_with_offset(&source).map_err(|(offset, error)| Error::ParseFile {
+        path: path.to_owned(),
+        offset,
+        error,
+    })?;
+    let mut login_users = vdf::LoginUser::from_vdf(&document)?;
+    Ok(f(&mut login_users))
+}
+
+/// Scans and parses the source text, returning the byte offset of the error along with it on failure.
+pub fn parse_with_offset(source: &[u8]) -> Result<vdf::Document<'_>, (usize, vdf::ScanParseError)> {
+    let mut scanner = Scanner::new(source);
+    let mut tokens = Vec::new();
+    while let Some(token) = scanner.next() {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(e) => return Err((scanner.start, e.into())),
+        }
+    }
+    let mut offset = 0;
+    vdf::parse(tokens.into_iter().inspect(|token| {
+        offset = token.lexeme.as_ptr() as usize - source.as_ptr() as usize;
+    }))
+    .map_err(|e| (offset, e.into()))
+}
+
+/// Gets the user marked as the one that most recently logged in, if any.
+///
+/// Malformed user entries are skipped. Fails with [`Error::AmbiguousMostRecent`] if several users are marked, since
+/// Steam marks one at most, and with [`Error::InvalidMostRecent`] if the marked user's username is invalid.
+pub fn most_recent(steam: &dyn SteamBackend) -> Result<Option<Username>, Error> {
+    let marked = with_login_users(steam, |login_users| {
+        login_users
+            .filter_map(Result::ok)
+            .filter(|user| user.most_recent)
+            .map(|user| user.username.to_vec())
+            .collect::<Vec<_>>()
+    })?;
+    match &marked[..] {
+        [] => Ok(None),
+        [username] => Username::try_from(username.as_slice())
+            .map(Some)
+            .map_err(Error::InvalidMostRecent),
+        marked => Err(Error::AmbiguousMostRecent(
+            marked
+                .iter()
+                .map(|username| username.escape_ascii().to_string())
+                .collect(),
+        )),
+    }
+}
+
+/// Finds the given user among the [login users](vdf::LoginUser) of the given Steam, and passes it to `f`, or
+/// [`None`] if it isn't registered.
+///
+/// Malformed user entries are skipped.
+pub fn with_user<R>(
+    steam: &dyn SteamBackend,
+    username: Username,
+    f: impl FnOnce(Option<&vdf::LoginUser>) -> R,
+) -> Result<R, Error> {
+    with_login_users(steam, |login_users| {
+        let user = login_users
+            .filter_map(Result::ok)
+            .find(|user| user.username.eq_ignore_ascii_case(username.as_bytes()));
+        f(user.as_ref())
+    })
+}
+
+/// Gets the SteamID of the given user, if registered.
+pub fn steam_id(steam: &dyn SteamBackend, username: Username) -> Result<Option<SteamId>, Error> {
+    with_user(steam, username, |user| {
+        user.and_then(vdf::LoginUser::steam_id)
+    })
+}
+
+/// Gets the nickname of the given user as is, if registered.
+pub fn nickname(steam: &dyn SteamBackend, username: Username) -> Result<Option<Vec<u8>>, Error> {
+    with_user(steam, username, |user| {
+        user.map(|user| user.nickname.to_vec())
+    })
+}
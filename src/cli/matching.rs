@@ -0,0 +1,133 @@
+//! Matching account names against the registered users.
+
+use diverter::vdf::LoginUser;
+
+/// The result of [matching](find) an account name.
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub enum Match<'a> {
+    /// The name matches a username exactly.
+    Exact(&'a [u8]),
+    /// The name is a prefix of a single username.
+    Prefix(&'a [u8]),
+    /// The name is a prefix of multiple usernames.
+    Ambiguous(Vec<&'a [u8]>),
+    /// The name matches no username.
+    None,
+}
+
+/// Matches an account name against usernames, case-insensitively.
+///
+/// Exact matches are preferred over prefix matches.
+pub fn find<'a>(name: &[u8], usernames: impl IntoIterator<Item = &'a [u8]>) -> Match<'a> {
+    let mut candidates = Vec::new();
+    for username in usernames {
+        if username.eq_ignore_ascii_case(name) {
+            return Match::Exact(username);
+        }
+        if username.len() > name.len() && username[..name.len()].eq_ignore_ascii_case(name) {
+            candidates.push(username);
+        }
+    }
+    match candidates.len() {
+        0 => Match::None,
+        1 => Match::Prefix(candidates[0]),
+        _ => Match::Ambiguous(candidates),
+    }
+}
+
+/// Checks whether the account name has the form of a SteamID64, i.e. 17 digits.
+pub fn is_steam_id64(name: &str) -> bool {
+    name.len() == 17 && name.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Finds the users whose nickname equals the given name, case-insensitively.
+///
+/// Nicknames that aren't valid UTF-8 are decoded lossily.
+pub fn find_by_nickname<'u, 'a>(name: &str, users: &'u [LoginUser<'a>]) -> Vec<&'u LoginUser<'a>> {
+    let name = name.to_lowercase();
+    users
+        .iter()
+        .filter(|user| String::from_utf8_lossy(user.nickname).to_lowercase() == name)
+        .collect()
+}
+
+/// Checks whether the user's username or nickname matches the given filter, case-insensitively.
+///
+/// A filter with `*` or `?` wildcards is a [glob] that must match the whole field, otherwise the field must contain
+/// the filter.
+pub fn filter(user: &LoginUser, filter: &str) -> bool {
+    let filter = filter.to_lowercase();
+    let is_glob = filter.contains(['*', '?']);
+    let pattern = filter.chars().collect::<Vec<_>>();
+    [user.username, user.nickname].into_iter().any(|field| {
+        let field = String::from_utf8_lossy(field).to_lowercase();
+        if is_glob {
+            glob(&pattern, &field.chars().collect::<Vec<_>>())
+        } else {
+            field.contains(&filter)
+        }
+    })
+}
+
+/// Matches text against a glob pattern, where `*` matches any run of characters and `?` any single character.
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // the position of the last `*` in the pattern, and the text position it's tried to match up to.
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // backtrack, letting the last `*` match one more character.
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Computes the Levenshtein edit distance between two names, case-insensitively.
+pub fn distance(a: &[u8], b: &[u8]) -> usize {
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(!ca.eq_ignore_ascii_case(&cb));
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Finds the usernames closest to the given name, for suggestions, closest first.
+///
+/// Only usernames within a few edits of the name are considered.
+pub fn closest<'a>(name: &[u8], usernames: impl IntoIterator<Item = &'a [u8]>) -> Vec<&'a [u8]> {
+    const MAX_SUGGESTIONS: usize = 3;
+    let max_distance = (name.len() / 3).max(2);
+    let mut candidates = usernames
+        .into_iter()
+        .map(|username| (distance(name, username), username))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|&(distance, _)| distance);
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, username)| username)
+        .collect()
+}
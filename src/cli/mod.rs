@@ -0,0 +1,30 @@
+//! Command-line interface helpers.
+
+pub mod archive;
+pub mod completions;
+pub mod config;
+#[cfg(windows)]
+pub mod daemon;
+pub mod doctor;
+pub mod dry_run;
+pub mod dump;
+pub mod kill;
+pub mod launch;
+pub mod list;
+pub mod logger;
+pub mod loginusers;
+pub mod matching;
+pub mod open;
+pub mod options;
+pub mod output;
+pub mod picker;
+pub mod porcelain;
+pub mod previous;
+pub mod rename;
+pub mod restart;
+pub mod sort;
+pub mod stats;
+pub mod status;
+pub mod steam;
+pub mod time;
+pub mod watch;
@@ -0,0 +1,88 @@
+//! Opening steam:// links.
+
+use std::{ffi::OsStr, process::ExitCode, time::Duration};
+
+use diverter::SteamBackend;
+
+use super::output::{error, info};
+
+/// How long to wait for a freshly launched Steam to log in before sending it the link.
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The named shortcuts, and the links they open.
+const SHORTCUTS: &[(&str, &str)] = &[
+    ("friends", "steam://open/friends"),
+    ("downloads", "steam://open/downloads"),
+    ("settings", "steam://open/settings"),
+    ("library", "steam://open/games"),
+    ("console", "steam://open/console"),
+];
+
+/// Parses an `open` target: a shortcut's name (case-insensitively) or a steam:// URL, into the URL to open.
+pub fn parse_target(target: &str) -> Result<String, String> {
+    if let Some((_, url)) = SHORTCUTS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(target))
+    {
+        return Ok((*url).to_owned());
+    }
+    let is_steam_url = target
+        .get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("steam://"));
+    if is_steam_url {
+        return Ok(target.to_owned());
+    }
+    let names = SHORTCUTS
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(format!("expected a steam:// URL or one of: {names}"))
+}
+
+/// Has Steam open the given URL, reporting the result to stderr.
+///
+/// If Steam isn't running, launches it first, and waits for it to log in, since it drops links it gets while it's
+/// starting up.
+pub fn open(url: &str) -> ExitCode {
+    let steam = match super::steam::find() {
+        Ok(steam) => steam,
+        Err(e) => {
+            error!("Failed to find Steam: {e}");
+            return ExitCode::from(&e);
+        }
+    };
+
+    match steam.is_running() {
+        Ok(true) => {}
+        Ok(false) => {
+            let before = steam.get_active_user().ok().flatten();
+            if let Err(e) = steam.launch_fast() {
+                error!("Failed to launch Steam: {e}");
+                return ExitCode::from(&e);
+            }
+            info!("🚀 launched Steam, waiting for it to log in..");
+            if super::restart::wait_for_login(&steam, before, READY_TIMEOUT).is_none() {
+                info!(
+                    "Steam didn't log in within {}s, opening the link anyway..",
+                    READY_TIMEOUT.as_secs()
+                );
+            }
+        }
+        Err(e) => {
+            error!("Failed to check whether Steam is running: {e}");
+            return ExitCode::from(&e);
+        }
+    }
+
+    match steam.launch_with_args(&[OsStr::new(url)]) {
+        Ok(()) => {
+            info!("🔗 opened {url}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("Failed to open {url}: {e}");
+            ExitCode::from(&e)
+        }
+    }
+}
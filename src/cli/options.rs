@@ -0,0 +1,138 @@
+//! Effective options, merged from the command-line arguments and the [configuration file](Config).
+
+use std::{ffi::OsStr, time::Duration};
+
+use super::config::{ColorMode, Config};
+
+/// Steam restart arguments.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::Args)]
+pub struct RestartArgs {
+    #[arg(short, long)]
+    /// Restart the Steam client ungracefully after setting the new user.
+    pub restart: bool,
+    /// Restarts the Steam client gracefully after setting the new user.
+    ///
+    /// Implies --restart.
+    #[arg(short, long)]
+    pub graceful: bool,
+    /// After restart, allows Steam to verify file integrity.
+    ///
+    /// Implies --restart.
+    #[arg(short, long)]
+    pub verify: bool,
+    /// After restart, starts Steam to the system tray without showing its window.
+    ///
+    /// Implies --restart.
+    #[arg(short, long)]
+    pub silent: bool,
+    /// How long to wait for a graceful restart to shut Steam down before falling back to killing it, in seconds.
+    #[arg(
+        long,
+        visible_alias = "force-after",
+        value_name = "SECS",
+        default_value_t = 10
+    )]
+    pub shutdown_timeout: u64,
+}
+
+/// Effective Steam restart options.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RestartOptions {
+    /// Whether to restart Steam.
+    pub restart: bool,
+    /// Whether to restart Steam gracefully.
+    pub graceful: bool,
+    /// Whether to let Steam verify its files after the restart.
+    pub verify: bool,
+    /// Whether to start Steam to the system tray after the restart.
+    pub silent: bool,
+    /// How long to wait for a graceful shutdown before killing Steam.
+    pub shutdown_timeout: Duration,
+    /// Whether to make Steam forget the password once it's stopped, before launching it again.
+    pub forget_password: bool,
+}
+
+impl RestartOptions {
+    /// Merges the restart arguments with the configuration defaults.
+    ///
+    /// Flags specified in the arguments take precedence. The restart flags imply a restart.
+    pub fn merge(args: RestartArgs, config: &Config) -> Self {
+        let graceful = args.graceful || config.graceful.unwrap_or(false);
+        let verify = args.verify || config.verify.unwrap_or(false);
+        let silent = args.silent || config.silent.unwrap_or(false);
+        Self {
+            restart: args.restart
+                || config.restart.unwrap_or(false)
+                || graceful
+                || verify
+                || silent,
+            graceful,
+            verify,
+            silent,
+            shutdown_timeout: Duration::from_secs(args.shutdown_timeout),
+            forget_password: false,
+        }
+    }
+}
+
+/// Merges the color argument with the configuration default.
+pub fn color(arg: Option<ColorMode>, config: &Config) -> ColorMode {
+    arg.or(config.color).unwrap_or_default()
+}
+
+/// Checks whether to color the output to the given stream.
+///
+/// Per <https://no-color.org>, `NO_COLOR` disables automatic color when set to a non-empty value.
+pub fn should_color(mode: ColorMode, stream: atty::Stream) -> bool {
+    mode.resolve(
+        is_no_color(std::env::var_os("NO_COLOR").as_deref()),
+        atty::is(stream),
+    )
+}
+
+/// Checks whether the given `NO_COLOR` environment variable value disables color, i.e. is set and not empty.
+fn is_no_color(value: Option<&OsStr>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_arg_over_config() {
+        let config = Config {
+            color: Some(ColorMode::Never),
+            ..Config::default()
+        };
+        assert_eq!(color(Some(ColorMode::Always), &config), ColorMode::Always);
+        assert_eq!(color(Some(ColorMode::Auto), &config), ColorMode::Auto);
+        assert_eq!(color(None, &config), ColorMode::Never);
+        assert_eq!(color(None, &Config::default()), ColorMode::Auto);
+    }
+
+    #[test]
+    fn no_color_value() {
+        assert!(!is_no_color(None));
+        assert!(!is_no_color(Some(OsStr::new(""))));
+        assert!(is_no_color(Some(OsStr::new("1"))));
+        assert!(is_no_color(Some(OsStr::new("0"))));
+    }
+
+    #[test]
+    fn auto_follows_no_color_and_terminal() {
+        assert!(ColorMode::Auto.resolve(false, true));
+        assert!(!ColorMode::Auto.resolve(true, true));
+        assert!(!ColorMode::Auto.resolve(false, false));
+        assert!(!ColorMode::Auto.resolve(true, false));
+    }
+
+    #[test]
+    fn explicit_mode_over_no_color_and_terminal() {
+        for (no_color, is_terminal) in [(false, false), (false, true), (true, false), (true, true)]
+        {
+            assert!(ColorMode::Always.resolve(no_color, is_terminal));
+            assert!(!ColorMode::Never.resolve(no_color, is_terminal));
+        }
+    }
+}
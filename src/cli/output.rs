@@ -0,0 +1,50 @@
+//! Reporting messages to stderr, subject to the quiet level.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The number of times `--quiet` was given.
+static QUIET: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the quiet level: 0 reports everything, 1 only errors, and 2 or more nothing.
+pub fn set_quiet(level: u8) {
+    QUIET.store(level, Ordering::Relaxed);
+}
+
+/// Whether informational messages, such as progress reports, and warnings are printed.
+pub fn info_enabled() -> bool {
+    QUIET.load(Ordering::Relaxed) == 0
+}
+
+/// Whether errors are printed.
+pub fn errors_enabled() -> bool {
+    QUIET.load(Ordering::Relaxed) < 2
+}
+
+/// Prints an informational message to stderr, unless quiet.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::cli::output::info_enabled() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Prints a warning to stderr, prefixed with "Warning: ", unless quiet.
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        if $crate::cli::output::info_enabled() {
+            eprintln!("Warning: {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Prints an error to stderr, unless quiet twice.
+macro_rules! error {
+    ($($arg:tt)*) => {
+        if $crate::cli::output::errors_enabled() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use {error, info, warning};
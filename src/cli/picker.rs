@@ -0,0 +1,212 @@
+//! Interactive account picker.
+
+#[cfg(windows)]
+use std::ffi::c_int;
+use std::io::{self, Write};
+#[cfg(not(windows))]
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+};
+
+use diverter::vdf::LoginUser;
+
+#[cfg(windows)]
+extern "C" {
+    /// Reads a key press from the console without echoing it (C runtime's `conio.h`).
+    fn _getch() -> c_int;
+}
+
+/// A key press relevant to the picker.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    Enter,
+    Cancel,
+    Digit(u8),
+    Other,
+}
+
+/// Reads a single key press from the console.
+#[cfg(windows)]
+fn read_key() -> Key {
+    // SAFETY: no preconditions.
+    match unsafe { _getch() } {
+        // extended keys are reported as a prefix followed by a scan code.
+        0 | 0xE0 => match unsafe { _getch() } {
+            72 => Key::Up,
+            80 => Key::Down,
+            _ => Key::Other,
+        },
+        0x0D | 0x0A => Key::Enter,
+        // Ctrl+C, Esc, q
+        0x03 | 0x1B | 0x71 => Key::Cancel,
+        c @ 0x30..=0x39 => Key::Digit(c as u8 - b'0'),
+        // vi-style navigation
+        0x6B => Key::Up,
+        0x6A => Key::Down,
+        _ => Key::Other,
+    }
+}
+
+/// Puts the terminal in a mode where key presses are read one at a time without echoing them, for as long as it
+/// lives, with `stty`.
+#[cfg(not(windows))]
+struct RawMode {
+    /// The terminal's settings to restore, as `stty -g` prints them.
+    saved: String,
+}
+
+#[cfg(not(windows))]
+impl RawMode {
+    /// Runs `stty` on the terminal of stdin with the given arguments, returning its output.
+    fn stty(args: &[&str]) -> io::Result<String> {
+        let output = Command::new("stty")
+            .args(args)
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!("stty {}", output.status)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    /// Enters the mode.
+    ///
+    /// Reads wait up to a tenth of a second, so a lone Esc can be told apart from the start of an escape sequence.
+    /// Ctrl+C is read as a key rather than interrupting, so the terminal is restored on cancel.
+    fn enter() -> io::Result<Self> {
+        let saved = Self::stty(&["-g"])?;
+        Self::stty(&["-icanon", "-echo", "-isig", "min", "0", "time", "1"])?;
+        Ok(Self { saved })
+    }
+}
+
+#[cfg(not(windows))]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Err(e) = Self::stty(&[&self.saved]) {
+            log::debug!("failed to restore the terminal: {e}");
+        }
+    }
+}
+
+/// Reads a single byte from stdin, or [`None`] if none came in time, per [`RawMode`].
+#[cfg(not(windows))]
+fn read_byte(stdin: &mut impl Read) -> Option<u8> {
+    let mut byte = 0;
+    match stdin.read(std::slice::from_mut(&mut byte)) {
+        Ok(1) => Some(byte),
+        _ => None,
+    }
+}
+
+/// Reads a single key press from the terminal, in [`RawMode`].
+#[cfg(not(windows))]
+fn read_key() -> Key {
+    let mut stdin = io::stdin().lock();
+    let byte = loop {
+        if let Some(byte) = read_byte(&mut stdin) {
+            break byte;
+        }
+    };
+    match byte {
+        // arrow keys are reported as escape sequences, as opposed to a lone Esc.
+        0x1B => match read_byte(&mut stdin) {
+            None => Key::Cancel,
+            Some(b'[' | b'O') => match read_byte(&mut stdin) {
+                Some(b'A') => Key::Up,
+                Some(b'B') => Key::Down,
+                _ => Key::Other,
+            },
+            Some(_) => Key::Other,
+        },
+        0x0D | 0x0A => Key::Enter,
+        0x03 | 0x71 => Key::Cancel,
+        c @ 0x30..=0x39 => Key::Digit(c - b'0'),
+        // vi-style navigation
+        0x6B => Key::Up,
+        0x6A => Key::Down,
+        _ => Key::Other,
+    }
+}
+
+/// Renders the menu lines.
+fn render(
+    out: &mut impl Write,
+    users: &[LoginUser],
+    highlighted: usize,
+    current: Option<usize>,
+    color: bool,
+) -> io::Result<()> {
+    for (i, user) in users.iter().enumerate() {
+        let highlight = i == highlighted;
+        writeln!(
+            out,
+            "\u{1B}[2K{ansi_start}{} {:>2}. {} {} ({}){ansi_end}",
+            if highlight { ">" } else { " " },
+            i + 1,
+            if Some(i) == current { "◼" } else { "◻" },
+            user.username.escape_ascii(),
+            user.nickname.escape_ascii(),
+            ansi_start = if color && highlight { "\u{1B}[7m" } else { "" },
+            ansi_end = if color && highlight { "\u{1B}[0m" } else { "" },
+        )?;
+    }
+    out.flush()
+}
+
+/// Interactively picks a user from the given list.
+///
+/// The `current` user is highlighted initially. Navigation is done with the arrow keys or by typing the
+/// entry's number, and the selection is confirmed with Enter.
+///
+/// Returns the picked index, or [`None`] if the user cancelled.
+pub fn pick(users: &[LoginUser], current: Option<usize>, color: bool) -> io::Result<Option<usize>> {
+    if users.is_empty() {
+        return Ok(None);
+    }
+
+    let mut out = io::stderr().lock();
+    writeln!(
+        out,
+        "Select an account (↑/↓ or number, Enter to confirm, Esc to cancel):"
+    )?;
+    #[cfg(not(windows))]
+    let _raw_mode = RawMode::enter()?;
+    let mut highlighted = current.unwrap_or(0);
+    let mut typed = 0usize;
+    render(&mut out, users, highlighted, current, color)?;
+
+    let picked = loop {
+        match read_key() {
+            Key::Up => {
+                typed = 0;
+                highlighted = highlighted.checked_sub(1).unwrap_or(users.len() - 1);
+            }
+            Key::Down => {
+                typed = 0;
+                highlighted = (highlighted + 1) % users.len();
+            }
+            Key::Digit(digit) => {
+                typed = typed * 10 + digit as usize;
+                if !(1..=users.len()).contains(&typed) {
+                    // start over from this digit if the accumulated number is out of range
+                    typed = digit as usize;
+                }
+                if (1..=users.len()).contains(&typed) {
+                    highlighted = typed - 1;
+                }
+            }
+            Key::Enter => break Some(highlighted),
+            Key::Cancel => break None,
+            Key::Other => continue,
+        }
+        write!(out, "\u{1B}[{}A", users.len())?;
+        render(&mut out, users, highlighted, current, color)?;
+    };
+
+    Ok(picked)
+}
@@ -0,0 +1,178 @@
+//! Stable output for scripts, with `--porcelain`.
+//!
+//! Each record is a line of tab-separated fields, in a fixed order that only grows by appending fields. Booleans are
+//! `1` or `0`, unknown fields are empty, and free text such as nicknames and paths is [encoded](encode). The format
+//! is documented in the README, so changes to it must be reflected there, and must not break existing fields.
+
+use std::fmt::Write;
+
+use diverter::{vdf::LoginUser, Username};
+
+use super::status::Status;
+
+/// Percent-encodes the bytes of free text that could break a record: control characters (including tabs and
+/// newlines), `%` and non-ASCII bytes.
+pub fn encode(text: &[u8]) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for &c in text {
+        if c == b'%' || c.is_ascii_control() || !c.is_ascii() {
+            let _ = write!(encoded, "%{c:02X}");
+        } else {
+            encoded.push(c as char);
+        }
+    }
+    encoded
+}
+
+/// Formats a boolean field.
+fn flag(value: bool) -> &'static str {
+    if value {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+/// Formats the current account for `get`: username, nickname, SteamID64, where the last two are empty if the user
+/// isn't registered.
+pub fn get(username: Username, user: Option<&LoginUser>) -> String {
+    format!(
+        "{username}\t{}\t{}",
+        user.map(|user| encode(user.nickname)).unwrap_or_default(),
+        user.map(|user| user.steamid.escape_ascii().to_string())
+            .unwrap_or_default(),
+    )
+}
+
+/// Formats a registered user for `list`: whether it's the auto-login user, username, nickname, SteamID64, last login
+/// time (seconds since the Unix epoch), whether it logged in most recently, whether Steam remembers its password.
+pub fn list_user(user: &LoginUser, selected: bool) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        flag(selected),
+        user.username.escape_ascii(),
+        encode(user.nickname),
+        user.steamid.escape_ascii(),
+        user.timestamp.map(|t| t.to_string()).unwrap_or_default(),
+        flag(user.most_recent),
+        flag(user.remember_password),
+    )
+}
+
+/// Formats Steam's state for `status`: installation directory, whether Steam is running, its PID, auto-login user,
+/// nickname.
+pub fn status(status: &Status) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        status
+            .path
+            .as_ref()
+            .map(|path| encode(path.as_bytes()))
+            .unwrap_or_default(),
+        status
+            .pid
+            .as_ref()
+            .map(|pid| flag(pid.is_some()))
+            .unwrap_or_default(),
+        match status.pid {
+            Ok(Some(pid)) => pid.to_string(),
+            _ => String::new(),
+        },
+        status
+            .username
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+        status
+            .nickname
+            .as_ref()
+            .map(|nickname| encode(nickname))
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(nickname: &[u8]) -> LoginUser<'_> {
+        LoginUser {
+            steamid: b"76561197960287930",
+            username: b"alice",
+            nickname,
+            allow_auto_login: true,
+            remember_password: true,
+            most_recent: false,
+            timestamp: Some(1_700_000_000),
+        }
+    }
+
+    fn alice() -> Username {
+        Username::try_from("alice").unwrap()
+    }
+
+    #[test]
+    fn encode_verbatim() {
+        assert_eq!(encode(b"Alice"), "Alice");
+        assert_eq!(encode(b"a\tb\nc%"), "a%09b%0Ac%25");
+        assert_eq!(encode("é".as_bytes()), "%C3%A9");
+        // invalid UTF-8 is kept as is rather than replaced.
+        assert_eq!(encode(b"\xFFx"), "%FFx");
+    }
+
+    #[test]
+    fn get_record() {
+        assert_eq!(
+            get(alice(), Some(&user(b"Alice \xFF"))),
+            "alice\tAlice %FF\t76561197960287930"
+        );
+        assert_eq!(get(alice(), None), "alice\t\t");
+    }
+
+    #[test]
+    fn list_record() {
+        assert_eq!(
+            list_user(&user(b"Alice\t1"), true),
+            "1\talice\tAlice%091\t76561197960287930\t1700000000\t0\t1"
+        );
+        let user = LoginUser {
+            timestamp: None,
+            most_recent: true,
+            remember_password: false,
+            ..user(b"")
+        };
+        assert_eq!(
+            list_user(&user, false),
+            "0\talice\t\t76561197960287930\t\t1\t0"
+        );
+    }
+
+    #[test]
+    fn status_record() {
+        let running = Status {
+            path: Ok(r"C:\Program Files (x86)\Steam".to_owned()),
+            pid: Ok(Some(1234)),
+            username: Ok(alice()),
+            nickname: Ok(b"Alice".to_vec()),
+        };
+        assert_eq!(
+            status(&running),
+            "C:\\Program Files (x86)\\Steam\t1\t1234\talice\tAlice"
+        );
+        let stopped = Status {
+            pid: Ok(None),
+            ..running
+        };
+        assert_eq!(
+            status(&stopped),
+            "C:\\Program Files (x86)\\Steam\t0\t\talice\tAlice"
+        );
+        let unknown = Status {
+            path: Err("not found".to_owned()),
+            pid: Err("not found".to_owned()),
+            username: Err("not set".to_owned()),
+            nickname: Err("not set".to_owned()),
+        };
+        assert_eq!(status(&unknown), "\t\t\t\t");
+    }
+}
@@ -0,0 +1,89 @@
+//! The previous auto-login user, remembered for switching back to it.
+
+use std::{fs, io, path::PathBuf};
+
+use diverter::{Username, UsernameError};
+
+/// A previous auto-login user state file error.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The state directory couldn't be determined.
+    #[cfg_attr(
+        windows,
+        error("couldn't locate the state directory, %LOCALAPPDATA% is not set")
+    )]
+    #[cfg_attr(
+        not(windows),
+        error("couldn't locate the state directory, $HOME is not set")
+    )]
+    NoStateDir,
+    /// Failed to read or write the state file.
+    #[error("failed to access the previous account file {}: {1}", .0.display())]
+    Io(PathBuf, io::Error),
+    /// The state file doesn't contain a valid username.
+    #[error("the previous account file {} is invalid: {1}", .0.display())]
+    Invalid(PathBuf, UsernameError),
+}
+
+/// Gets the platform's directory for per-user state files.
+///
+/// That's `%LOCALAPPDATA%` on Windows.
+#[cfg(windows)]
+fn state_dir() -> Option<PathBuf> {
+    std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+}
+
+/// Gets the platform's directory for per-user state files.
+///
+/// That's `~/Library/Application Support` on macOS.
+#[cfg(target_os = "macos")]
+fn state_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(
+        PathBuf::from(home)
+            .join("Library")
+            .join("Application Support"),
+    )
+}
+
+/// Gets the platform's directory for per-user state files.
+///
+/// That's `$XDG_STATE_HOME`, or `~/.local/state` if it's unset or not absolute, per the XDG Base Directory
+/// Specification.
+#[cfg(not(any(windows, target_os = "macos")))]
+fn state_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            let home = std::env::var_os("HOME")?;
+            Some(PathBuf::from(home).join(".local").join("state"))
+        })
+}
+
+/// Gets the path to the state file (`diverter/previous` in the [state directory](state_dir)).
+pub fn path() -> Result<PathBuf, Error> {
+    let dir = state_dir().ok_or(Error::NoStateDir)?;
+    Ok(dir.join("diverter").join("previous"))
+}
+
+/// Loads the previous auto-login user, or [`None`] if none was remembered yet.
+pub fn load() -> Result<Option<Username>, Error> {
+    let path = path()?;
+    match fs::read_to_string(&path) {
+        Ok(source) => Username::try_from_trimmed(&source)
+            .map(Some)
+            .map_err(|e| Error::Invalid(path, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::Io(path, e)),
+    }
+}
+
+/// Remembers the given previous auto-login user.
+pub fn save(username: Username) -> Result<(), Error> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::Io(path.clone(), e))?;
+    }
+    fs::write(&path, username.as_display()).map_err(|e| Error::Io(path, e))
+}
@@ -0,0 +1,86 @@
+//! Renaming accounts' nicknames in loginusers.vdf.
+
+use std::{fs, io, path::PathBuf, process::ExitCode};
+
+use diverter::{vdf, Username};
+
+use super::{dry_run::DryRun, loginusers};
+
+/// Failure to rename an account.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Failed to load the login users.
+    #[error("{0}")]
+    LoginUsers(#[from] loginusers::Error),
+    /// Failed to check whether Steam is running.
+    #[error("failed to check whether Steam is running: {0}")]
+    IsRunning(diverter::Error),
+    /// Steam is running, and would overwrite the change.
+    #[error("Steam is running and would overwrite the change, close it and try again")]
+    SteamRunning,
+    /// The user isn't registered in loginusers.vdf.
+    #[error("{0} isn't registered in the logged in users data")]
+    NoUser(Username),
+    /// Failed to back up loginusers.vdf.
+    #[error("failed to back up the logged in users data to {}: {1}", .0.display())]
+    Backup(PathBuf, io::Error),
+    /// Failed to write loginusers.vdf.
+    #[error("failed to write the logged in users data: {0}")]
+    Write(io::Error),
+}
+
+/// Exit codes per `sysexits.h`.
+impl<'a> From<&'a Error> for ExitCode {
+    fn from(e: &'a Error) -> Self {
+        match e {
+            Error::LoginUsers(e) => ExitCode::from(e),
+            Error::IsRunning(e) => ExitCode::from(e),
+            Error::SteamRunning => ExitCode::from(75),
+            Error::NoUser(_) => ExitCode::from(67),
+            Error::Backup(..) => ExitCode::from(73),
+            Error::Write(_) => ExitCode::from(74),
+        }
+    }
+}
+
+/// Replaces the nickname of the given user in the loginusers.vdf source, leaving the rest of it intact.
+fn replace_nickname(source: &[u8], username: Username, nickname: &str) -> Result<Vec<u8>, Error> {
+    let document = vdf::scan_parse(source).map_err(loginusers::Error::from)?;
+    let user = vdf::LoginUser::from_vdf(&document)
+        .map_err(loginusers::Error::from)?
+        .filter_map(Result::ok)
+        .find(|user| user.username.eq_ignore_ascii_case(username.as_bytes()))
+        .ok_or(Error::NoUser(username))?;
+
+    // parsed values borrow the source, so the nickname's position is its offset from the source
+    let start = user.nickname.as_ptr() as usize - source.as_ptr() as usize;
+    let end = start + user.nickname.len();
+    let escaped = vdf::escape(nickname.as_bytes());
+
+    let mut renamed = Vec::with_capacity(source.len() - user.nickname.len() + escaped.len());
+    renamed.extend_from_slice(&source[..start]);
+    renamed.extend_from_slice(&escaped);
+    renamed.extend_from_slice(&source[end..]);
+    Ok(renamed)
+}
+
+/// Sets the nickname of the given user in loginusers.vdf, backing up the original file first.
+///
+/// Refuses to rename while Steam is running, since Steam would overwrite the change.
+pub fn rename(username: Username, nickname: &str, dry_run: DryRun) -> Result<(), Error> {
+    let steam = loginusers::find_steam()?;
+    if steam.is_running().map_err(Error::IsRunning)? {
+        return Err(Error::SteamRunning);
+    }
+
+    let path = loginusers::path(&steam);
+    let source = fs::read(&path).map_err(loginusers::Error::Read)?;
+    let renamed = replace_nickname(&source, username, nickname)?;
+
+    if dry_run.allows(format_args!("rename {username} to {nickname}")) {
+        let backup = path.with_extension("vdf.bak");
+        fs::copy(&path, &backup).map_err(|e| Error::Backup(backup, e))?;
+        fs::write(&path, renamed).map_err(Error::Write)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,373 @@
+//! Restarting Steam.
+
+use std::{
+    ffi::{OsStr, OsString},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use diverter::SteamBackend;
+
+use super::{
+    options::RestartOptions,
+    output::{error, info},
+    stats::Stats,
+};
+
+/// The exit code for when the account was switched, but launching the requested app failed.
+pub const EXIT_APP_LAUNCH_FAILED: u8 = 75;
+
+/// The exit code for when the account was switched, but it couldn't be confirmed that Steam logged into it, or
+/// Steam didn't log in in time.
+pub const EXIT_UNCONFIRMED: u8 = 76;
+
+/// How long to wait for Steam to start before launching an app.
+const APP_LAUNCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Waits for Steam to run, and then has it launch the app of the given ID.
+fn launch_app(steam: &dyn SteamBackend, app_id: u32) -> diverter::Result<()> {
+    let start = Instant::now();
+    while !steam.is_running()? && start.elapsed() < APP_LAUNCH_TIMEOUT {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    steam.launch_with_args(&[OsStr::new("-applaunch"), OsStr::new(&app_id.to_string())])
+}
+
+/// Kills Steam, reporting the result.
+fn kill(steam: &dyn SteamBackend) {
+    match steam.kill_any() {
+        Ok(true) => info!("🔪 Steam has been killed"),
+        Ok(false) => info!("Steam wasn't running"),
+        Err(e) => {
+            error!("Failed to kill Steam to restart it ({e}). Will still try to launch it..")
+        }
+    }
+}
+
+/// Polls until Steam is [fully stopped](SteamBackend::is_fully_stopped), or the deadline passes, so Steam isn't
+/// relaunched while its helpers still hold the account.
+fn wait_for_helpers(steam: &dyn SteamBackend, deadline: Instant) {
+    loop {
+        match steam.is_fully_stopped() {
+            Ok(true) => break,
+            Ok(false) if Instant::now() >= deadline => {
+                info!("Steam's helper processes are still running, launching it anyway..");
+                break;
+            }
+            Ok(false) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                log::debug!("failed to check for Steam's helper processes: {e}");
+                break;
+            }
+        }
+    }
+}
+
+/// How Steam was [stopped](stop).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stopped {
+    /// Left alone, since it wasn't running.
+    NotRunning,
+    /// Killed outright, since the options aren't graceful.
+    Killed,
+    /// Shut down gracefully.
+    ShutDown,
+    /// Killed, since it didn't shut down within the shutdown timeout.
+    KilledAfterTimeout,
+    /// Killed, since shutting it down failed.
+    KilledAfterError,
+}
+
+/// Stops Steam to restart it, reporting which way it was stopped to stderr.
+///
+/// Does nothing if Steam isn't running, e.g. so a graceful shutdown doesn't launch it. If the options are graceful, shuts Steam down gracefully, and falls back to killing it if it doesn't shut down
+/// within the shutdown timeout, or the shutdown fails. After a graceful shutdown, also waits out the rest of the
+/// timeout for Steam's helper processes to exit. Otherwise, kills Steam outright.
+///
+/// Records the duration of each step in `stats`.
+pub fn stop(steam: &dyn SteamBackend, options: RestartOptions, stats: &mut Stats) -> Stopped {
+    match stats.time("process enumeration", || steam.is_running()) {
+        Ok(false) => {
+            info!("Steam wasn't running");
+            return Stopped::NotRunning;
+        }
+        Ok(true) => {}
+        Err(e) => log::debug!("failed to check whether Steam is running, stopping it anyway: {e}"),
+    }
+    if !options.graceful {
+        stats.time("kill", || kill(steam));
+        return Stopped::Killed;
+    }
+    let deadline = Instant::now() + options.shutdown_timeout;
+    match stats.time("shutdown", || steam.shutdown()).and_then(|()| {
+        stats.time("wait for exit", || {
+            steam.wait_for_exit(options.shutdown_timeout)
+        })
+    }) {
+        Ok(true) => {
+            info!("🛑 Steam has been shut down");
+            stats.time("wait for helpers", || wait_for_helpers(steam, deadline));
+            Stopped::ShutDown
+        }
+        Ok(false) => {
+            info!(
+                "Steam didn't shut down within {}s, falling back to killing it..",
+                options.shutdown_timeout.as_secs()
+            );
+            stats.time("kill", || kill(steam));
+            Stopped::KilledAfterTimeout
+        }
+        Err(e) => {
+            error!("Failed to shut down Steam gracefully ({e}), falling back to killing it..");
+            stats.time("kill", || kill(steam));
+            Stopped::KilledAfterError
+        }
+    }
+}
+
+/// Restarts the given Steam per the given options and with the given arguments, reporting progress to stderr, and
+/// then launches the given app, if any.
+///
+/// If the options [forget the password](RestartOptions::forget_password), it's done while Steam is stopped, since
+/// Steam rewrites it as it shuts down. Failing that, Steam is still launched, but the exit code reports the failure.
+///
+/// Records the duration of each phase in `stats`.
+pub fn restart(
+    steam: &dyn SteamBackend,
+    options: RestartOptions,
+    args: &[OsString],
+    app_id: Option<u32>,
+    stats: &mut Stats,
+) -> ExitCode {
+    stop(steam, options, stats);
+
+    let forgot = if options.forget_password {
+        stats
+            .time("forget password", || steam.set_remember_password(false))
+            .map_err(|e| {
+                error!("Failed to make Steam forget the password ({e}). Will still launch it..");
+                ExitCode::from(&e)
+            })
+    } else {
+        Ok(())
+    };
+
+    let launch_result = stats.time("launch", || {
+        match (options.silent, options.verify, args.is_empty()) {
+            (false, true, true) => steam.launch(),
+            (false, false, true) => steam.launch_fast(),
+            (silent, verify, _) => {
+                let mut launch_args = Vec::with_capacity(args.len() + 2);
+                if silent {
+                    launch_args.push(OsStr::new("-silent"));
+                }
+                if !verify {
+                    launch_args.push(OsStr::new("-noverifyfiles"));
+                }
+                launch_args.extend(args.iter().map(OsString::as_os_str));
+                steam.launch_with_args(&launch_args)
+            }
+        }
+    });
+    match launch_result {
+        Ok(()) => info!("🚀 launched Steam"),
+        Err(e) => {
+            error!("Failed to re-launch Steam: {e}");
+            if app_id.is_some() {
+                return ExitCode::from(EXIT_APP_LAUNCH_FAILED);
+            }
+        }
+    }
+
+    if let Some(app_id) = app_id {
+        match stats.time("app launch", || launch_app(steam, app_id)) {
+            Ok(()) => info!("🎮 launched app {app_id}"),
+            Err(e) => {
+                error!("Failed to launch app {app_id}: {e}");
+                return ExitCode::from(EXIT_APP_LAUNCH_FAILED);
+            }
+        }
+    }
+
+    forgot.err().unwrap_or(ExitCode::SUCCESS)
+}
+
+/// Polls until the given Steam is logged into the account of the given ID, or the timeout elapses.
+///
+/// On timeout, returns the account ID Steam was last seen logged into, if any.
+pub fn confirm(
+    steam: &dyn SteamBackend,
+    account_id: u32,
+    timeout: Duration,
+) -> Result<(), Option<u32>> {
+    let start = Instant::now();
+    loop {
+        let active = steam.get_active_user().ok().flatten();
+        if active == Some(account_id) {
+            break Ok(());
+        }
+        if start.elapsed() >= timeout {
+            break Err(active);
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Polls until the given, freshly launched, Steam logs into any account, or the timeout elapses.
+///
+/// `before` is the account ID read before launching Steam. A killed Steam leaves its last account behind, so a login
+/// only counts once the active account changed from it, and then names an account.
+///
+/// Returns the account ID Steam logged into, if it did in time.
+pub fn wait_for_login(
+    steam: &dyn SteamBackend,
+    before: Option<u32>,
+    timeout: Duration,
+) -> Option<u32> {
+    poll_login(
+        || steam.get_active_user().ok().flatten(),
+        before,
+        timeout,
+        Duration::from_millis(500),
+    )
+}
+
+/// Polls the active account per [`wait_for_login`], reading it with `active_user` every `interval`.
+fn poll_login(
+    mut active_user: impl FnMut() -> Option<u32>,
+    before: Option<u32>,
+    timeout: Duration,
+    interval: Duration,
+) -> Option<u32> {
+    let start = Instant::now();
+    let mut changed = false;
+    loop {
+        let active = active_user();
+        changed |= active != before;
+        if changed && active.is_some() {
+            break active;
+        }
+        if start.elapsed() >= timeout {
+            break None;
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diverter::mock::MockSteam;
+
+    use super::*;
+
+    fn graceful(shutdown_timeout: Duration) -> RestartOptions {
+        RestartOptions {
+            restart: true,
+            graceful: true,
+            shutdown_timeout,
+            ..RestartOptions::default()
+        }
+    }
+
+    #[test]
+    fn kill() {
+        let steam = MockSteam::new().with_running(true);
+        let options = RestartOptions {
+            restart: true,
+            ..RestartOptions::default()
+        };
+        let mut stats = Stats::default();
+        assert_eq!(stop(&steam, options, &mut stats), Stopped::Killed);
+        assert!(!steam.is_running().unwrap());
+        assert_eq!(
+            stats.phases().collect::<Vec<_>>(),
+            ["process enumeration", "kill"]
+        );
+    }
+
+    #[test]
+    fn shut_down() {
+        let steam = MockSteam::new().with_running(true);
+        let timeout = Duration::from_secs(10);
+        let mut stats = Stats::default();
+        let start = Instant::now();
+        assert_eq!(
+            stop(&steam, graceful(timeout), &mut stats),
+            Stopped::ShutDown
+        );
+        assert!(start.elapsed() < timeout);
+        assert!(!steam.is_running().unwrap());
+        assert_eq!(
+            stats.phases().collect::<Vec<_>>(),
+            [
+                "process enumeration",
+                "shutdown",
+                "wait for exit",
+                "wait for helpers"
+            ]
+        );
+    }
+
+    #[test]
+    fn kill_after_timeout() {
+        let steam = MockSteam::new()
+            .with_running(true)
+            .with_shutdown_ignored(true);
+        let timeout = Duration::from_millis(250);
+        let mut stats = Stats::default();
+        let start = Instant::now();
+        assert_eq!(
+            stop(&steam, graceful(timeout), &mut stats),
+            Stopped::KilledAfterTimeout
+        );
+        assert!(start.elapsed() >= timeout);
+        assert!(!steam.is_running().unwrap());
+        assert_eq!(
+            stats.phases().collect::<Vec<_>>(),
+            ["process enumeration", "shutdown", "wait for exit", "kill"]
+        );
+    }
+
+    #[test]
+    fn not_running() {
+        let steam = MockSteam::new();
+        let mut stats = Stats::default();
+        assert_eq!(
+            stop(&steam, graceful(Duration::from_secs(10)), &mut stats),
+            Stopped::NotRunning
+        );
+        assert_eq!(stats.phases().collect::<Vec<_>>(), ["process enumeration"]);
+    }
+
+    /// Polls the given readings of the active account in turn, repeating the last one.
+    fn poll_readings(readings: &[Option<u32>], before: Option<u32>) -> Option<u32> {
+        let mut readings = readings.iter().copied();
+        let mut last = None;
+        let active_user = || {
+            last = readings.next().unwrap_or(last);
+            last
+        };
+        poll_login(
+            active_user,
+            before,
+            Duration::from_millis(50),
+            Duration::ZERO,
+        )
+    }
+
+    #[test]
+    fn login_after_stopped() {
+        assert_eq!(poll_readings(&[None, None, Some(7)], None), Some(7));
+    }
+
+    #[test]
+    fn stale_login_after_kill() {
+        // the killed Steam's account lingers until the new Steam resets it.
+        assert_eq!(poll_readings(&[Some(7), Some(7)], Some(7)), None);
+        assert_eq!(
+            poll_readings(&[Some(7), None, None, Some(7)], Some(7)),
+            Some(7)
+        );
+        assert_eq!(poll_readings(&[Some(7), Some(9)], Some(7)), Some(9));
+    }
+}
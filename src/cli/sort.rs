@@ -0,0 +1,52 @@
+//! Sorting listed users.
+
+use std::cmp::Ordering;
+
+/// An order to list the users in.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Sort {
+    /// Most recently logged in first. Users with an unknown last login time are listed last.
+    Recent,
+    /// Alphabetically by username (case-insensitive).
+    Name,
+}
+
+/// A user's sort keys: their username and last login timestamp.
+pub type Key<'a> = (&'a [u8], Option<u64>);
+
+impl Sort {
+    /// Compares two users by their sort keys.
+    ///
+    /// If `reverse`, the order is reversed, except that users with an unknown last login time remain last.
+    pub fn compare(self, a: Key, b: Key, reverse: bool) -> Ordering {
+        let ordering = match self {
+            Self::Recent => match (a.1, b.1) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            Self::Name => {
+                a.0.iter()
+                    .map(u8::to_ascii_lowercase)
+                    .cmp(b.0.iter().map(u8::to_ascii_lowercase))
+            }
+        };
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Sorts the users in the given order, or reverses their order if `reverse` and there's no given order.
+///
+/// The sort is stable, so users that compare equal keep their order.
+pub fn sort<T>(users: &mut [T], sort: Option<Sort>, reverse: bool, key: impl Fn(&T) -> Key) {
+    match sort {
+        Some(sort) => users.sort_by(|a, b| sort.compare(key(a), key(b), reverse)),
+        None if reverse => users.reverse(),
+        None => {}
+    }
+}
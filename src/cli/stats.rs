@@ -0,0 +1,42 @@
+//! Timing the phases of a switch, for `--stats`.
+
+use std::time::{Duration, Instant};
+
+use super::output::info;
+
+/// The durations of the phases of a switch, such as the registry write and Steam's shutdown, in the order they ran.
+#[derive(Debug, Default, Clone)]
+pub struct Stats {
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Stats {
+    /// Runs `f`, recording how long it took as the given phase.
+    pub fn time<R>(&mut self, phase: &'static str, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.phases.push((phase, start.elapsed()));
+        result
+    }
+
+    /// Gets the names of the recorded phases, in the order they ran.
+    #[cfg(test)]
+    pub fn phases(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.phases.iter().map(|&(phase, _)| phase)
+    }
+
+    /// Prints each phase's duration to stderr, one per line, followed by the total, unless quiet.
+    pub fn print(&self) {
+        let total = self.phases.iter().map(|&(_, duration)| duration).sum();
+        let width = self
+            .phases
+            .iter()
+            .map(|(phase, _)| phase.len())
+            .chain(["total".len()])
+            .max()
+            .unwrap_or(0);
+        for (phase, duration) in self.phases.iter().chain([&("total", total)]) {
+            info!("{phase:<width$}  {:>9.3}s", duration.as_secs_f64());
+        }
+    }
+}
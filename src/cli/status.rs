@@ -0,0 +1,104 @@
+//! A summary of Steam's state.
+
+use std::borrow::Cow;
+
+use diverter::{Steam, Username};
+
+use super::loginusers;
+
+/// Steam's state, where each field is either known or the reason it isn't.
+#[derive(Debug)]
+pub struct Status {
+    /// The Steam installation directory.
+    pub path: Result<String, String>,
+    /// The process ID of the Steam client, or [`None`] if it's not running.
+    pub pid: Result<Option<u32>, String>,
+    /// The auto-login user.
+    pub username: Result<Username, String>,
+    /// The auto-login user's nickname, as is.
+    pub nickname: Result<Vec<u8>, String>,
+}
+
+impl Status {
+    /// Gathers Steam's state, field by field.
+    pub fn gather() -> Self {
+        let steam = super::steam::find().map_err(|e| e.to_string());
+        let path = steam
+            .as_ref()
+            .map(|steam| steam.path().display().to_string())
+            .map_err(Clone::clone);
+        let pid = steam
+            .as_ref()
+            .map_err(Clone::clone)
+            .and_then(|steam| steam.pid().map_err(|e| e.to_string()));
+        let username = Steam::get_auto_login_user().map_err(|e| e.to_string());
+        let nickname = match (&steam, &username) {
+            (Ok(steam), Ok(username)) => loginusers::nickname(steam, *username)
+                .map_err(|e| e.to_string())
+                .and_then(|nickname| {
+                    nickname.ok_or_else(|| "not in the logged in users data".to_owned())
+                }),
+            (Err(e), Ok(_)) => Err(format!("failed to find Steam: {e}")),
+            (_, Err(_)) => Err("no auto-login user".to_owned()),
+        };
+        Self {
+            path,
+            pid,
+            username,
+            nickname,
+        }
+    }
+
+    /// Prints the state as a human-readable block.
+    pub fn print_text(&self) {
+        let unknown = |e: &String| format!("unknown ({e})");
+        println!(
+            "Steam:       {}",
+            self.path.as_ref().map_or_else(unknown, Clone::clone)
+        );
+        println!(
+            "Running:     {}",
+            match &self.pid {
+                Ok(Some(pid)) => format!("yes (PID {pid})"),
+                Ok(None) => "no".to_owned(),
+                Err(e) => unknown(e),
+            }
+        );
+        println!(
+            "Auto-login:  {}",
+            self.username
+                .as_ref()
+                .map_or_else(unknown, ToString::to_string)
+        );
+        println!(
+            "Nickname:    {}",
+            self.nickname
+                .as_ref()
+                .map_or_else(unknown, |nickname| String::from_utf8_lossy(nickname)
+                    .into_owned())
+        );
+    }
+
+    /// Prints the state as a JSON object, where unknown fields are `null`.
+    pub fn print_json(&self) -> serde_json::Result<()> {
+        /// The JSON object of the state.
+        #[derive(serde::Serialize)]
+        struct Json<'a> {
+            path: Option<&'a str>,
+            running: Option<bool>,
+            pid: Option<u32>,
+            auto_login_user: Option<Username>,
+            nickname: Option<Cow<'a, str>>,
+        }
+
+        let json = serde_json::to_string(&Json {
+            path: self.path.as_deref().ok(),
+            running: self.pid.as_ref().ok().map(Option::is_some),
+            pid: self.pid.as_ref().ok().copied().flatten(),
+            auto_login_user: self.username.as_ref().ok().copied(),
+            nickname: self.nickname.as_deref().ok().map(String::from_utf8_lossy),
+        })?;
+        println!("{json}");
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Locating Steam, honoring the `--steam-path` override.
+
+use std::{ffi::OsStr, fs::File, path::PathBuf, sync::OnceLock, time::Duration};
+
+use diverter::{Steam, SteamBackend, Username};
+
+/// The Steam installation directory to use instead of the one in the registry.
+static STEAM_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Overrides the Steam installation directory for [`find`].
+///
+/// Only the first override takes effect.
+pub fn set_path(path: PathBuf) {
+    let _ = STEAM_PATH.set(path);
+}
+
+/// Finds Steam, in the [overridden](set_path) directory if any, or else per the registry.
+pub fn find() -> diverter::Result<Steam> {
+    match STEAM_PATH.get() {
+        Some(path) => Steam::from_dir(path),
+        None => Steam::new(),
+    }
+}
+
+/// A [`SteamBackend`] that [finds](find) Steam only once an operation needs its installation, so operations on the
+/// auto-login user alone, which is in the registry, work even where Steam can't be found.
+#[derive(Debug, Default)]
+pub struct LazySteam(OnceLock<Steam>);
+
+impl LazySteam {
+    /// Gets the found Steam, finding it on first use.
+    fn steam(&self) -> diverter::Result<&Steam> {
+        if let Some(steam) = self.0.get() {
+            return Ok(steam);
+        }
+        let steam = find()?;
+        Ok(self.0.get_or_init(|| steam))
+    }
+}
+
+impl SteamBackend for LazySteam {
+    #[inline]
+    fn get_auto_login_user(&self) -> diverter::Result<Username> {
+        Steam::get_auto_login_user()
+    }
+
+    #[inline]
+    fn get_auto_login_user_raw(&self) -> diverter::Result<Vec<u8>> {
+        Steam::get_auto_login_user_raw()
+    }
+
+    #[inline]
+    fn set_auto_login_user(&self, username: Username) -> diverter::Result<()> {
+        Steam::set_auto_login_user(username)
+    }
+
+    #[inline]
+    fn clear_auto_login_user(&self) -> diverter::Result<()> {
+        Steam::clear_auto_login_user()
+    }
+
+    fn vdf_loginusers(&self) -> diverter::Result<File> {
+        self.steam()?.vdf_loginusers()
+    }
+
+    fn launch(&self) -> diverter::Result<()> {
+        self.steam()?.launch()
+    }
+
+    fn launch_with_args(&self, args: &[&OsStr]) -> diverter::Result<()> {
+        self.steam()?.launch_with_args(args)
+    }
+
+    fn launch_fast(&self) -> diverter::Result<()> {
+        SteamBackend::launch_fast(self.steam()?)
+    }
+
+    #[inline]
+    fn set_remember_password(&self, remember: bool) -> diverter::Result<()> {
+        Steam::set_remember_password(remember)
+    }
+
+    #[inline]
+    fn get_active_user(&self) -> diverter::Result<Option<u32>> {
+        Steam::get_active_user()
+    }
+
+    fn kill(&self) -> diverter::Result<usize> {
+        self.steam()?.kill()
+    }
+
+    fn shutdown(&self) -> diverter::Result<()> {
+        SteamBackend::shutdown(self.steam()?)
+    }
+
+    fn is_running(&self) -> diverter::Result<bool> {
+        self.steam()?.is_running()
+    }
+
+    fn kill_with_helpers(&self, with_helpers: bool) -> diverter::Result<usize> {
+        SteamBackend::kill_with_helpers(self.steam()?, with_helpers)
+    }
+
+    fn pid(&self) -> diverter::Result<Option<u32>> {
+        self.steam()?.pid()
+    }
+
+    fn client_version(&self) -> diverter::Result<String> {
+        self.steam()?.client_version()
+    }
+
+    #[inline]
+    fn check_registry_access(&self) -> diverter::Result<()> {
+        Steam::check_registry_access()
+    }
+
+    fn wait_for_exit(&self, timeout: Duration) -> diverter::Result<bool> {
+        SteamBackend::wait_for_exit(self.steam()?, timeout)
+    }
+
+    fn is_fully_stopped(&self) -> diverter::Result<bool> {
+        SteamBackend::is_fully_stopped(self.steam()?)
+    }
+}
@@ -0,0 +1,92 @@
+//! Time formatting.
+
+use chrono::{Local, TimeZone};
+
+/// Formats a Unix timestamp as a local date and time.
+///
+/// Returns [`None`] if the timestamp is out of range.
+pub fn local_datetime(timestamp: u64) -> Option<String> {
+    let datetime = Local
+        .timestamp_opt(i64::try_from(timestamp).ok()?, 0)
+        .single()?;
+    Some(datetime.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Formats a Unix timestamp relative to `now` (also a Unix timestamp), e.g. "5 minutes ago", falling back to the
+/// local date for timestamps over 30 days old, or in the future.
+pub fn relative(timestamp: u64, now: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    let ago = |count: u64, unit: &str| {
+        let plural = if count == 1 { "" } else { "s" };
+        format!("{count} {unit}{plural} ago")
+    };
+    match now.checked_sub(timestamp) {
+        Some(elapsed) if elapsed < MINUTE => "just now".to_owned(),
+        Some(elapsed) if elapsed < HOUR => ago(elapsed / MINUTE, "minute"),
+        Some(elapsed) if elapsed < DAY => ago(elapsed / HOUR, "hour"),
+        Some(elapsed) if elapsed < 30 * DAY => ago(elapsed / DAY, "day"),
+        _ => local_date(timestamp).unwrap_or_else(|| "unknown".to_owned()),
+    }
+}
+
+/// Formats a Unix timestamp as a local date.
+///
+/// Returns [`None`] if the timestamp is out of range.
+fn local_date(timestamp: u64) -> Option<String> {
+    let datetime = Local
+        .timestamp_opt(i64::try_from(timestamp).ok()?, 0)
+        .single()?;
+    Some(datetime.format("%Y-%m-%d").to_string())
+}
+
+/// Gets the current Unix timestamp.
+pub fn now() -> u64 {
+    Local::now().timestamp().try_into().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    #[test]
+    fn minutes() {
+        assert_eq!(relative(NOW, NOW), "just now");
+        assert_eq!(relative(NOW - 59, NOW), "just now");
+        assert_eq!(relative(NOW - 60, NOW), "1 minute ago");
+        assert_eq!(relative(NOW - 2 * MINUTE, NOW), "2 minutes ago");
+    }
+
+    #[test]
+    fn hours() {
+        assert_eq!(relative(NOW - 59 * MINUTE, NOW), "59 minutes ago");
+        assert_eq!(relative(NOW - 60 * MINUTE, NOW), "1 hour ago");
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(relative(NOW - 23 * HOUR, NOW), "23 hours ago");
+        assert_eq!(relative(NOW - 24 * HOUR, NOW), "1 day ago");
+        assert_eq!(relative(NOW - 29 * DAY, NOW), "29 days ago");
+    }
+
+    #[test]
+    fn date_fallback() {
+        let month_ago = NOW - 30 * DAY;
+        assert_eq!(relative(month_ago, NOW), local_date(month_ago).unwrap());
+    }
+
+    #[test]
+    fn future() {
+        let later = NOW + MINUTE;
+        assert_eq!(relative(later, NOW), local_date(later).unwrap());
+        assert_eq!(relative(u64::MAX, NOW), "unknown");
+    }
+}
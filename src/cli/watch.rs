@@ -0,0 +1,160 @@
+//! Watching for auto-login user changes.
+
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use diverter::{RegistryWatch, Steam, Username};
+#[cfg(windows)]
+use winapi::{
+    shared::minwindef::{BOOL, DWORD, FALSE, TRUE},
+    um::{
+        consoleapi::SetConsoleCtrlHandler,
+        wincon::{CTRL_BREAK_EVENT, CTRL_C_EVENT},
+    },
+};
+
+use super::output::{error, info};
+
+/// Whether Ctrl+C (or Ctrl+Break) was pressed.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Marks [`INTERRUPTED`] on Ctrl+C or Ctrl+Break.
+#[cfg(windows)]
+unsafe extern "system" fn on_ctrl(ctrl_type: DWORD) -> BOOL {
+    if ctrl_type == CTRL_C_EVENT || ctrl_type == CTRL_BREAK_EVENT {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+        TRUE
+    } else {
+        FALSE
+    }
+}
+
+/// Has Ctrl+C (or Ctrl+Break) mark [`INTERRUPTED`] rather than terminate the process.
+#[cfg(windows)]
+fn catch_interrupts() {
+    // SAFETY: the handler only touches an atomic.
+    unsafe { SetConsoleCtrlHandler(Some(on_ctrl), TRUE) };
+}
+
+/// Leaves Ctrl+C to terminate the process, as there's nothing to clean up on the way out.
+#[cfg(not(windows))]
+fn catch_interrupts() {}
+
+/// How often to check for an interrupt while waiting for a change.
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The output format of change events.
+#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// A human-readable line per change.
+    #[default]
+    Text,
+    /// A JSON object per line per change.
+    Json,
+}
+
+/// Gets the current auto-login user, or [`None`] if there is none.
+fn current() -> diverter::Result<Option<Username>> {
+    match Steam::get_auto_login_user() {
+        Ok(username) => Ok(Some(username)),
+        Err(diverter::Error::InvalidUsernameInRegistry(_)) => Ok(None),
+        Err(diverter::Error::ReadSteamRegistry(e)) if e.kind() == io::ErrorKind::NotFound => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Prints a change event to stdout.
+fn print_change(format: Format, old: Option<Username>, new: Option<Username>) {
+    let now = Local::now();
+    match format {
+        Format::Text => {
+            let text = |username: Option<Username>| match username {
+                Some(username) => username.to_string(),
+                None => "(none)".to_owned(),
+            };
+            println!(
+                "{} {} -> {}",
+                now.format("%Y-%m-%d %H:%M:%S"),
+                text(old),
+                text(new)
+            );
+        }
+        Format::Json => {
+            /// The JSON object of a change.
+            #[derive(serde::Serialize)]
+            struct Change {
+                timestamp: String,
+                old: Option<Username>,
+                new: Option<Username>,
+            }
+
+            let change = Change {
+                timestamp: now.to_rfc3339(),
+                old,
+                new,
+            };
+            match serde_json::to_string(&change) {
+                Ok(json) => println!("{json}"),
+                Err(e) => error!("Failed to format the change as JSON: {e}"),
+            }
+        }
+    }
+}
+
+/// Prints auto-login user changes until interrupted by Ctrl+C.
+///
+/// Changes are detected with a [`RegistryWatch`], or by polling at the given interval, if any.
+pub fn watch(format: Format, poll: Option<Duration>) -> diverter::Result<()> {
+    catch_interrupts();
+
+    let watch = match poll {
+        Some(_) => None,
+        None => Some(RegistryWatch::new()?),
+    };
+
+    let mut last = current()?;
+    match last {
+        Some(username) => {
+            info!("👀 watching for auto-login user changes from {username}, press Ctrl+C to stop..")
+        }
+        None => info!("👀 watching for auto-login user changes, press Ctrl+C to stop.."),
+    }
+    loop {
+        // arming before reading ensures changes made after the read aren't missed.
+        if let Some(watch) = &watch {
+            watch.arm()?;
+        }
+        let username = current()?;
+        if username != last {
+            print_change(format, last, username);
+            last = username;
+        }
+
+        let start = Instant::now();
+        loop {
+            if INTERRUPTED.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            match &watch {
+                Some(watch) => {
+                    if watch.wait(INTERRUPT_CHECK_INTERVAL)? {
+                        break;
+                    }
+                }
+                None => {
+                    let remaining = poll.unwrap_or_default().saturating_sub(start.elapsed());
+                    if remaining.is_zero() {
+                        break;
+                    }
+                    std::thread::sleep(remaining.min(INTERRUPT_CHECK_INTERVAL));
+                }
+            }
+        }
+    }
+}
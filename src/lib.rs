@@ -1,14 +1,33 @@
 //! Switch Steam accounts.
 
-#[cfg(not(target_os = "windows"))]
-compile_error!("Only Windows is supported.");
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+compile_error!("Only Windows, Linux and macOS are supported.");
 
 mod username;
 pub use username::{Username, UsernameError};
 
+#[cfg(windows)]
+mod process;
+
+mod steamid;
+pub use steamid::SteamId;
+
+#[cfg(windows)]
 mod steam;
-pub use steam::{Error, Result, Steam};
+#[cfg(windows)]
+pub use steam::{registry, Error, RegistryWatch, Result, Steam};
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod unix;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub use unix::{Error, RegistryWatch, Result, Steam};
+
+mod backend;
+pub use backend::SteamBackend;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
 pub mod vdf;
 
-mod util;
+pub mod util;
@@ -1,162 +1,1898 @@
-use std::{io::Read, process::ExitCode, time::Duration};
+#[cfg(windows)]
+use std::collections::BTreeMap;
+use std::{
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
 
-use clap::Parser;
-use diverter::{vdf, Steam, Username};
+use clap::{error::ErrorKind, parser::ValueSource, CommandFactory, FromArgMatches};
+use clap_complete::ArgValueCandidates;
+use diverter::{Steam, SteamBackend, Username, UsernameError};
+
+mod cli;
+use cli::{
+    config::{ColorMode, Config},
+    dry_run::DryRun,
+    loginusers,
+    matching::{self, Match},
+    options::{self, RestartArgs, RestartOptions},
+    output::{error, info, warning},
+    stats::Stats,
+};
 
 #[derive(clap::Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     command: Command,
-    /// Print with color. Leave unspecified for auto.
-    #[arg(short, long)]
-    color: Option<bool>,
+    /// When to print with color. Unless given, the configuration file's default applies.
+    #[arg(short, long, value_enum, value_name = "WHEN", default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+    /// Ignore the configuration file.
+    #[cfg(feature = "toml")]
+    #[arg(long)]
+    no_config: bool,
+    /// Print the changes that would be made instead of making them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// Use the Steam installation in DIR instead of the one in the registry.
+    #[arg(long, global = true, value_name = "DIR")]
+    steam_path: Option<PathBuf>,
+    /// Don't print informational messages and warnings. Specify twice to not print errors either, and rely on the
+    /// exit code.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    quiet: u8,
+    /// Print the output of get, list and status in a stable, tab-separated format for scripts, documented in the
+    /// README.
+    #[arg(long, global = true)]
+    porcelain: bool,
+    /// Log diagnostic events, such as registry accesses and killed processes, to stderr. Specify twice for more
+    /// detail.
+    #[arg(long, global = true, action = clap::ArgAction::Count)]
+    debug: u8,
 }
 
-#[derive(Debug, Clone, Copy, clap::Subcommand)]
+#[derive(Debug, Clone, clap::Subcommand)]
 enum Command {
-    #[command(alias = "g")]
+    #[command(alias = "g", alias = "whoami")]
     /// Prints the current account.
-    Get,
+    ///
+    /// Exits with code 67 if there's none, which -q reports silently.
+    Get(GetArgs),
     /// Sets to the account of USERNAME.
     #[command(alias = "s")]
-    Set {
-        /// The username of the account to switch to.
-        username: Username,
+    Set(SetArgs),
+    /// Switches back to the previous account.
+    #[command(alias = "t")]
+    Toggle {
+        #[command(flatten)]
+        restart: RestartArgs,
+    },
+    /// Unsets the auto-login user, so Steam asks which account to log into on its next launch.
+    #[command(alias = "logout")]
+    Clear {
+        #[command(flatten)]
+        restart: RestartArgs,
+    },
+    /// Lists registered Steam users.
+    #[command(alias = "l", alias = "ls")]
+    List(ListArgs),
+    /// Launches Steam without switching accounts.
+    Launch {
+        /// Allow Steam to verify its files.
         #[arg(short, long)]
-        /// Restart the Steam client ungracefully after setting the new user.
-        restart: bool,
-        /// Restarts the Steam client gracefully after setting the new user.
+        verify: bool,
+        /// Launch Steam even if it's already running.
         ///
-        /// Implies --restart.
+        /// Otherwise, exits with code 75 if Steam is already running.
         #[arg(short, long)]
-        graceful: bool,
-        /// After restart, allows Steam to verify file integrity.
+        force: bool,
+        /// Arguments to pass to Steam, after --, e.g. `diverter launch -- -silent`.
+        #[arg(last = true, value_name = "STEAM_ARGS")]
+        args: Vec<OsString>,
+    },
+    /// Has Steam open a view, by a shortcut's name or a steam:// URL, e.g. `diverter open friends`.
+    ///
+    /// Launches Steam first if it isn't running, waiting for it to log in.
+    Open {
+        /// A steam:// URL, or one of the shortcuts: friends, downloads, settings, library, console.
+        #[arg(value_parser = cli::open::parse_target, value_name = "TARGET")]
+        url: String,
+    },
+    /// Stops Steam, killing it unless --graceful is specified.
+    Kill {
+        /// Shut Steam down gracefully instead of killing it.
         ///
-        /// Implies --restart.
+        /// Exits with code 75 if Steam doesn't shut down in time.
         #[arg(short, long)]
-        verify: bool,
+        graceful: bool,
+        /// How long to wait for Steam to shut down gracefully, in seconds.
+        #[arg(long, value_name = "SECS", default_value_t = 10, requires = "graceful")]
+        shutdown_timeout: u64,
+        /// Kill Steam if it doesn't shut down gracefully in time.
+        #[arg(long, requires = "graceful")]
+        then_kill: bool,
+        /// Also kill Steam's helper processes, such as the Steam Client Service.
+        #[arg(long)]
+        with_helpers: bool,
     },
-    /// Lists registered Steam users.
-    #[command(alias = "l", alias = "ls")]
+    /// Prints a summary of Steam's state: its location, whether it's running, and the auto-login user.
+    Status {
+        /// Print as a JSON object, with null for unknown fields.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Changes the nickname Steam shows for an account, in its logged in users data.
+    ///
+    /// Steam must be closed, since it would overwrite the change. A backup of the data is saved beside it.
+    Rename {
+        /// The username of the account to rename.
+        #[arg(add = ArgValueCandidates::new(cli::completions::usernames))]
+        username: String,
+        /// The new nickname.
+        nickname: String,
+    },
+    /// Prints a line whenever the auto-login user changes, until interrupted with Ctrl+C.
+    Watch {
+        /// The output format of each change.
+        #[arg(long, value_enum, default_value_t = cli::watch::Format::Text)]
+        format: cli::watch::Format,
+        /// Poll for changes every MS milliseconds instead of watching Steam's registry for them.
+        #[arg(long, value_name = "MS")]
+        poll: Option<u64>,
+    },
+    /// Prints the aliases, the auto-login user and the registered users, for importing on another machine.
+    Export {
+        /// The output format.
+        #[arg(long, value_enum, default_value_t = cli::archive::Format::Json)]
+        format: cli::archive::Format,
+    },
+    /// Restores the aliases, and optionally the auto-login user, from a FILE made by `export`.
+    ///
+    /// Invalid entries are skipped, in which case the exit code is 65.
+    Import {
+        /// The exported file, read as TOML if its extension is `.toml` and the `toml` feature is enabled, and as JSON otherwise.
+        file: PathBuf,
+        /// Also set the auto-login user to the exported one.
+        #[arg(long)]
+        auto_login: bool,
+    },
+    /// Checks the environment (Steam's registry subkey, installation, logged in users data, auto-login user and
+    /// process), printing whether each check passed, for diagnosing problems.
+    ///
+    /// Exits with code 69 if any check failed.
+    Doctor,
+    /// Stays running and switches accounts, restarting Steam gracefully, when their configured hotkeys are pressed.
+    ///
+    /// Hotkeys are configured in the `hotkeys` table of the configuration file. Stop with Ctrl+C.
+    #[cfg(windows)]
+    Daemon,
+    /// Manages account aliases.
+    #[cfg(feature = "toml")]
+    #[command(alias = "a")]
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommand,
+    },
+    /// Prints a completion script for SHELL.
+    #[command(hide = true)]
+    Completions {
+        /// The shell to complete for.
+        shell: clap_complete::Shell,
+    },
+    /// Prints the tokens of a VDF FILE, for debugging the VDF scanner.
+    #[command(hide = true)]
+    DumpTokens {
+        /// The VDF file to scan.
+        file: PathBuf,
+    },
+}
+
+/// The arguments of `get`.
+#[derive(Debug, Clone, clap::Args)]
+struct GetArgs {
+    /// Print the account's nickname instead of its username.
+    #[arg(short, long, conflicts_with = "full")]
+    nickname: bool,
+    /// Print the account's SteamID in the given format instead of its username.
+    ///
+    /// Exits with code 67 if the account isn't registered in Steam's logged in users data. With --porcelain,
+    /// prints the get record regardless of the format, whose SteamID field is the SteamID64.
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["nickname", "full"])]
+    steamid: Option<SteamIdFormat>,
+    /// Print the account's username followed by its nickname.
+    #[arg(short, long)]
+    full: bool,
+    /// Where to read the current account from.
+    #[arg(long, value_enum, default_value_t = Source::Registry)]
+    source: Source,
+    /// Also print the source the account was read from, to stderr.
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// The arguments of `set`.
+#[derive(Debug, Clone, clap::Args)]
+struct SetArgs {
+    /// The username or alias of the account to switch to.
+    ///
+    /// Leave unspecified to pick from the registered users interactively.
+    #[arg(add = ArgValueCandidates::new(cli::completions::usernames))]
+    username: Option<String>,
+    /// Read the username or alias from stdin, e.g. piped from another picker, rather than from USERNAME.
+    ///
+    /// Stdin must hold a single line.
+    #[arg(long, conflicts_with = "username")]
+    stdin: bool,
+    /// Switch to the account Steam marks as the most recent in its logged in users data, i.e. the one it last
+    /// logged into, e.g. manually, so the auto-login user agrees with it.
+    ///
+    /// Exits with code 67 if no account is marked, or 65 if several are.
+    #[arg(long, conflicts_with_all = ["username", "stdin", "exact", "by_nickname"])]
+    most_recent: bool,
+    /// Use the username as given, rather than matching it against the registered users.
+    ///
+    /// Allows switching to accounts that haven't logged in on this machine.
+    #[arg(short, long, visible_alias = "no-check")]
+    exact: bool,
+    /// Match the account by its nickname rather than its username.
+    ///
+    /// Nicknames are also matched when no username matches.
+    #[arg(long, conflicts_with = "exact")]
+    by_nickname: bool,
+    #[command(flatten)]
+    restart: RestartArgs,
+    /// Restart Steam even if the account is already the auto-login user.
+    ///
+    /// Otherwise, setting the current account does nothing.
+    #[arg(long, visible_alias = "force-restart")]
+    force: bool,
+    /// After restarting, launch the app (e.g. a game) of the given ID.
+    ///
+    /// Implies --restart. Exits with code 75 if the account was switched but the app failed to launch.
+    #[arg(long, value_name = "APPID")]
+    launch_app: Option<u32>,
+    /// Pass ARG to Steam when restarting it. Can be repeated, e.g. `--launch-arg -applaunch --launch-arg 730`.
+    ///
+    /// Implies --restart.
+    #[arg(long = "launch-arg", value_name = "ARG", allow_hyphen_values = true)]
+    launch_args: Vec<OsString>,
+    /// After restarting, wait for Steam to log into the account and report whether it did.
+    ///
+    /// Implies --restart. Exits with code 76 if the login couldn't be confirmed.
+    #[arg(long)]
+    confirm: bool,
+    /// How long to wait for the login with --confirm, in seconds.
+    #[arg(long, value_name = "SECS", default_value_t = 60, requires = "confirm")]
+    confirm_timeout: u64,
+    /// After restarting, wait up to SECS seconds (default 60) for Steam to log into the account.
+    ///
+    /// Implies --restart. Exits with code 76 if Steam didn't log into it in time.
+    #[arg(
+        long,
+        value_name = "SECS",
+        num_args = 0..=1,
+        default_missing_value = "60",
+        conflicts_with = "confirm"
+    )]
+    wait: Option<u64>,
+    /// Don't read the auto-login user back after setting it.
+    ///
+    /// Otherwise, exits with code 71 before restarting if another process changed it in the meantime.
+    #[arg(long)]
+    no_verify_write: bool,
+    /// Print how long each phase of the switch took, e.g. the registry write, Steam's shutdown and launch, to
+    /// stderr at the end.
+    #[arg(long)]
+    stats: bool,
+    /// Make Steam prompt for the account's password on its next launch.
+    ///
+    /// With a restart, it's done once Steam is stopped, since Steam may remember the password again as it shuts
+    /// down.
+    #[arg(long)]
+    forget_password: bool,
+}
+
+/// The arguments of `list`.
+#[derive(Debug, Clone, clap::Args)]
+struct ListArgs {
+    /// Only list users whose username or nickname contains FILTER (case-insensitive).
+    ///
+    /// FILTER may be a glob with `*` and `?` wildcards instead, matching the whole username or nickname.
+    #[arg(short, long)]
+    filter: Option<String>,
+    /// Also print each user's SteamID, last login time, whether they logged in most recently, and whether Steam
+    /// remembers their password.
+    #[arg(short, long)]
+    verbose: bool,
+    /// The output format.
+    #[arg(long, value_enum, default_value_t = cli::list::Format::Text, conflicts_with = "verbose")]
+    format: cli::list::Format,
+    /// The order to list the users in. Leave unspecified for Steam's order.
+    #[arg(long, value_enum)]
+    sort: Option<cli::sort::Sort>,
+    /// List the users in reverse order.
+    #[arg(long)]
+    reverse: bool,
+    /// Print just the number of users, counting only those matching --filter, if given.
+    ///
+    /// Exits with code 65 if some user entries are malformed, after printing the count of the well-formed ones.
+    #[arg(long, conflicts_with_all = ["verbose", "format"])]
+    count: bool,
+    /// List the users in the given loginusers.vdf FILE, e.g. a backup from another machine, instead of Steam's.
+    ///
+    /// Works without Steam installed, and doesn't mark the auto-login user.
+    #[arg(long, value_name = "FILE")]
+    file: Option<PathBuf>,
+}
+
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, clap::Subcommand)]
+enum AliasCommand {
+    /// Adds an ALIAS for the account of USERNAME.
+    Add {
+        /// The alias name.
+        alias: String,
+        /// The username of the account the alias stands for.
+        #[arg(add = ArgValueCandidates::new(cli::completions::usernames))]
+        username: Username,
+    },
+    /// Removes an ALIAS.
+    #[command(alias = "rm")]
+    Remove {
+        /// The alias name.
+        alias: String,
+    },
+    /// Lists the aliases.
+    #[command(alias = "ls")]
     List,
 }
 
-fn main() -> ExitCode {
-    let cli = Cli::parse();
+/// Where `get` reads the current account from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Source {
+    /// The account Steam will automatically log into next, per the registry.
+    Registry,
+    /// The account Steam most recently logged into, per loginusers.vdf.
+    ///
+    /// Differs from the registry's after logging into another account through Steam without diverter. Exits with
+    /// code 65 if several accounts are marked as the most recent.
+    Vdf,
+}
 
-    match cli.command {
-        Command::Get => match Steam::get_auto_login_user() {
-            Ok(username) => println!("{username}"),
-            Err(e) => eprintln!("Error: {e}"),
-        },
-        Command::Set {
-            username,
-            restart,
-            graceful,
-            verify,
-        } => {
-            if let Err(e) = Steam::set_auto_login_user(username) {
-                eprintln!("Failed to set the new username: {e}");
-                return ExitCode::from(&e);
-            }
-            if restart || graceful || verify {
-                match Steam::new() {
-                    Ok(steam) => {
-                        let (kill_method, kill_method_verb, kill_symbol, kill_result) = if graceful
-                        {
-                            (
-                                "shut down",
-                                "shut down",
-                                "🛑",
-                                steam.shutdown_poll(Duration::from_millis(100)),
-                            )
-                        } else {
-                            ("killed", "kill", "🔪", steam.kill().map(|_| ()))
-                        };
+/// A SteamID representation for `get --steamid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SteamIdFormat {
+    /// The SteamID64, e.g. 76561197960287930.
+    #[value(name = "64")]
+    Id64,
+    /// The legacy representation, e.g. STEAM_1:0:11101.
+    Steam2,
+    /// The bracketed representation, e.g. [U:1:22202].
+    Steam3,
+    /// The account ID, the lower 32 bits of the SteamID64, e.g. 22202.
+    #[value(name = "accountid")]
+    AccountId,
+}
 
-                        match kill_result {
-                                Ok(()) => eprintln!("{kill_symbol} Steam has been {kill_method}"),
-                                Err(e) => eprintln!("Failed to {kill_method_verb} Steam to restart it ({e}). Will still try to launch it..")
-                            }
+/// Exits with a usage error for an invalid USERNAME argument.
+fn invalid_username(name: &str, e: UsernameError) -> ! {
+    Cli::command()
+        .error(
+            ErrorKind::ValueValidation,
+            format!("invalid value '{name}' for '[USERNAME]': {e}"),
+        )
+        .exit()
+}
 
-                        let launch_result = if verify {
-                            steam.launch()
-                        } else {
-                            steam.launch_fast()
-                        };
-                        match launch_result {
-                            Ok(()) => eprintln!("🚀 launched Steam"),
-                            Err(e) => {
-                                eprintln!("Failed to re-launch Steam: {e}");
+/// Resolves the account name given to `set` to a [`Username`].
+///
+/// Aliases are resolved as is. Otherwise, unless `exact`, the name is matched against the registered users
+/// case-insensitively, either exactly or as a prefix of a single username, and failing that, by nickname.
+/// If `by_nickname`, the name is only matched by nickname.
+///
+/// A name of 17 digits is taken for a SteamID64, and resolved to the username registered under it, unless it's
+/// itself a registered username.
+fn resolve_username(
+    steam: &dyn SteamBackend,
+    name: &str,
+    exact: bool,
+    by_nickname: bool,
+    config: &Config,
+) -> Result<Username, ExitCode> {
+    let name = name.trim_matches(|c: char| c.is_ascii_whitespace());
+    if !by_nickname && (exact || config.aliases.contains_key(name)) {
+        return Ok(config
+            .resolve(name)
+            .unwrap_or_else(|e| invalid_username(name, e)));
+    }
+
+    let matched = loginusers::with_login_users(steam, |login_users| {
+        let users = login_users.filter_map(Result::ok).collect::<Vec<_>>();
+        // a registered username takes precedence over a SteamID64 of the same digits.
+        if !by_nickname
+            && matching::is_steam_id64(name)
+            && !users
+                .iter()
+                .any(|user| user.username.eq_ignore_ascii_case(name.as_bytes()))
+        {
+            return match users.iter().find(|user| user.steamid == name.as_bytes()) {
+                Some(user) => {
+                    info!("Matched {} by SteamID", user.username.escape_ascii());
+                    Ok(Username::try_from(user.username))
+                }
+                None => {
+                    error!("No registered account has the SteamID {name}. The registered SteamIDs are:");
+                    for user in &users {
+                        error!(
+                            "  {} ({})",
+                            user.steamid.escape_ascii(),
+                            user.username.escape_ascii()
+                        );
+                    }
+                    Err(ExitCode::from(67))
+                }
+            };
+        }
+        let by_username = if by_nickname {
+            Match::None
+        } else {
+            matching::find(name.as_bytes(), users.iter().map(|user| user.username))
+        };
+        match by_username {
+            Match::Exact(username) => Ok(Username::try_from(username)),
+            Match::Prefix(username) => {
+                info!("Matched {}", username.escape_ascii());
+                Ok(Username::try_from(username))
+            }
+            Match::Ambiguous(candidates) => {
+                error!("The account name {name} is ambiguous, it matches:");
+                for candidate in candidates {
+                    error!("  {}", candidate.escape_ascii());
+                }
+                Err(ExitCode::from(65))
+            }
+            Match::None => match matching::find_by_nickname(name, &users)[..] {
+                [user] => {
+                    if !by_nickname {
+                        info!("Matched {} by nickname", user.username.escape_ascii());
+                    }
+                    Ok(Username::try_from(user.username))
+                }
+                [] if by_nickname => {
+                    error!("No registered account has the nickname {name}.");
+                    Err(ExitCode::from(67))
+                }
+                [] => {
+                    error!("No registered account matches {name}.");
+                    match matching::closest(name.as_bytes(), users.iter().map(|user| user.username))
+                        [..]
+                    {
+                        [] => {}
+                        [suggestion] => error!("Did you mean {}?", suggestion.escape_ascii()),
+                        ref suggestions => {
+                            error!("Did you mean one of:");
+                            for suggestion in suggestions {
+                                error!("  {}", suggestion.escape_ascii());
                             }
                         }
                     }
-                    Err(e) => {
-                        eprintln!("Failed to find Steam to restart it: {e}");
+                    error!("Pass --no-check to switch to it anyway.");
+                    Err(ExitCode::from(67))
+                }
+                ref users => {
+                    error!("The nickname {name} is ambiguous, it belongs to:");
+                    for user in users {
+                        error!("  {}", user.username.escape_ascii());
                     }
+                    Err(ExitCode::from(65))
                 }
-            }
+            },
         }
-        Command::List => match Steam::new() {
-            Ok(steam) => match steam.vdf_loginusers() {
-                Ok(mut vdf_file) => {
-                    let should_color = cli.color.unwrap_or_else(|| atty::is(atty::Stream::Stdout));
-                    let mut vdf_source = String::with_capacity(4096);
-                    if let Err(e) = vdf_file.read_to_string(&mut vdf_source) {
-                        eprintln!("Failed to read logged in users data: {e}");
-                        return ExitCode::from(69);
-                    }
+    });
+    match matched {
+        Ok(username) => Ok(username?.unwrap_or_else(|e| invalid_username(name, e))),
+        Err(e) if by_nickname => {
+            error!("Error: {e}");
+            Err(ExitCode::from(&e))
+        }
+        Err(e) => {
+            warning!("couldn't match the account name against the registered users ({e}).");
+            Ok(config
+                .resolve(name)
+                .unwrap_or_else(|e| invalid_username(name, e)))
+        }
+    }
+}
 
-                    match vdf::scan_parse(vdf_source.as_bytes()) {
-                        Ok(document) => match vdf::LoginUser::from_vdf(&document) {
-                            Ok(login_users) => {
-                                let existing_username = Steam::get_auto_login_user().ok();
-                                let existing_username = existing_username
-                                    .as_ref()
-                                    .map(|username| username.as_bytes());
-
-                                login_users.for_each(|user| match user {
-                                    Ok(user) => {
-                                        let selected = Some(user.username) == existing_username;
-                                        println!(
-                                            "{ansi_start}{} {} ({}){ansi_end}",
-                                            if selected { "◼" } else { "◻" },
-                                            user.username.escape_ascii(),
-                                            user.nickname.escape_ascii(),
-                                            ansi_start = if should_color && selected {
-                                                "\u{1B}[32m"
-                                            } else {
-                                                ""
-                                            },
-                                            ansi_end = if should_color { "\u{1B}[0m" } else { "" },
-                                        )
-                                    }
-                                    Err(e) => eprintln!("Failed to read user entry: {e}"),
-                                });
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to parse logged in users data: {e}");
-                                return ExitCode::from(69);
+/// Gets the user marked as the most recent in loginusers.vdf, reporting the failure on error, or if no user is
+/// marked.
+fn resolve_most_recent(steam: &dyn SteamBackend) -> Result<Username, ExitCode> {
+    match loginusers::most_recent(steam) {
+        Ok(Some(username)) => {
+            info!("Matched {username}, the most recent account");
+            Ok(username)
+        }
+        Ok(None) => {
+            error!("Error: no account is marked as the most recent in the logged in users data.");
+            Err(ExitCode::from(67))
+        }
+        Err(e) => {
+            error!("Error: {e}");
+            Err(ExitCode::from(&e))
+        }
+    }
+}
+
+/// The exit code of `get` when there's no current account.
+const EXIT_NO_CURRENT: u8 = 67;
+
+/// Gets the current account from the given source, reporting the failure on error.
+///
+/// The lack of a current account is reported as information rather than an error, so `-q` silences it. An invalid
+/// auto-login user is printed as is to `out` for diagnosis, or just reported with `porcelain`, which has no record
+/// for it.
+fn get_current(
+    steam: &dyn SteamBackend,
+    source: Source,
+    verbose: bool,
+    porcelain: bool,
+    out: &mut impl Write,
+) -> Result<Username, ExitCode> {
+    match source {
+        Source::Registry => {
+            if verbose {
+                info!("Reading the auto-login user from the registry.");
+            }
+            match steam.get_auto_login_user() {
+                Ok(username) => Ok(username),
+                Err(diverter::Error::ReadSteamRegistry(e))
+                    if e.kind() == io::ErrorKind::NotFound =>
+                {
+                    info!("No auto-login user is set.");
+                    Err(ExitCode::from(EXIT_NO_CURRENT))
+                }
+                Err(e @ diverter::Error::InvalidUsernameInRegistry(_)) => {
+                    match steam.get_auto_login_user_raw() {
+                        Ok(raw) if porcelain => error!("Error: {e}: {}", raw.escape_ascii()),
+                        Ok(raw) => {
+                            warning!("{e}, printing it as is.");
+                            if let Err(e) = writeln!(out, "{}", raw.escape_ascii()) {
+                                error!("Failed to print the auto-login user: {e}");
+                                return Err(ExitCode::from(74));
                             }
-                        },
-                        Err(e) => {
-                            eprintln!("Failed to parse logged in users data: {e}");
-                            return ExitCode::from(69);
                         }
+                        Err(_) => error!("Error: {e}"),
                     }
+                    Err(ExitCode::from(&e))
                 }
                 Err(e) => {
-                    eprintln!("Failed to find logged in users data: {e}");
-                    return ExitCode::from(&e);
+                    error!("Error: {e}");
+                    Err(ExitCode::from(&e))
+                }
+            }
+        }
+        Source::Vdf => {
+            if verbose {
+                info!("Reading the most recent user from loginusers.vdf.");
+            }
+            match loginusers::most_recent(steam) {
+                Ok(Some(username)) => Ok(username),
+                Ok(None) => {
+                    info!("No user is marked as the most recent in the logged in users data.");
+                    Err(ExitCode::from(EXIT_NO_CURRENT))
+                }
+                Err(e) => {
+                    error!("Error: {e}");
+                    Err(ExitCode::from(&e))
+                }
+            }
+        }
+    }
+}
+
+/// Reads the account name given to `set --stdin`, which must be a single line.
+fn read_stdin_name() -> Result<String, ExitCode> {
+    let input = io::read_to_string(io::stdin()).map_err(|e| {
+        error!("Failed to read the username from stdin: {e}");
+        ExitCode::from(74)
+    })?;
+    let mut lines = input.lines().map(|line| line.trim_end_matches('\r'));
+    match (lines.next(), lines.next()) {
+        (Some(name), None) if !name.is_empty() => Ok(name.to_owned()),
+        (None | Some(""), None) => {
+            error!("Error: expected a username on stdin, but it's empty.");
+            Err(ExitCode::from(65))
+        }
+        _ => {
+            error!("Error: expected a single username on stdin, but got multiple lines.");
+            Err(ExitCode::from(65))
+        }
+    }
+}
+
+/// Sets the auto-login user, remembering the replaced one for `toggle`.
+///
+/// If `verify` is set, reads the auto-login user back and fails if it isn't the set one, without remembering the
+/// replaced one.
+fn switch(
+    steam: &dyn SteamBackend,
+    username: Username,
+    verify: bool,
+    dry_run: DryRun,
+) -> Result<(), ExitCode> {
+    if !dry_run.allows(format_args!("set the auto-login user to {username}")) {
+        return Ok(());
+    }
+    let previous = match steam.set_auto_login_user_with_backup(username) {
+        Ok(previous) => previous,
+        Err(e) => {
+            error!("Failed to set the new username: {e}");
+            return Err(ExitCode::from(&e));
+        }
+    };
+    if verify {
+        if let Err(e) = steam.verify_auto_login_user(username) {
+            error!("Error: {e}");
+            return Err(ExitCode::from(&e));
+        }
+    }
+    if let Some(previous) = previous.filter(|&previous| previous != username) {
+        if let Err(e) = cli::previous::save(previous) {
+            warning!("failed to remember the previous account: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// Reads the [login users](diverter::vdf::LoginUser) to list out of the given loginusers.vdf-format file, or else
+/// out of the given Steam's, and passes them to `f` along with the auto-login user to mark, which is [`None`] for a
+/// file.
+fn with_listed_users<R>(
+    steam: &dyn SteamBackend,
+    file: Option<&Path>,
+    f: impl FnOnce(
+        &mut dyn Iterator<Item = Result<diverter::vdf::LoginUser, diverter::vdf::LoginUserVdfError>>,
+        Option<Username>,
+    ) -> R,
+) -> Result<R, loginusers::Error> {
+    match file {
+        Some(path) => loginusers::with_login_users_in(path, |login_users| f(login_users, None)),
+        None => {
+            let selected = steam.get_auto_login_user().ok();
+            loginusers::with_login_users(steam, |login_users| f(login_users, selected))
+        }
+    }
+}
+
+/// Collects the login users matching the filter, if any, apart from the malformed user entries.
+fn collect_users<'a>(
+    login_users: &mut dyn Iterator<
+        Item = Result<diverter::vdf::LoginUser<'a>, diverter::vdf::LoginUserVdfError>,
+    >,
+    filter: Option<&str>,
+) -> (
+    Vec<diverter::vdf::LoginUser<'a>>,
+    Vec<diverter::vdf::LoginUserVdfError>,
+) {
+    let mut users = Vec::new();
+    let mut errors = Vec::new();
+    for user in login_users {
+        match user {
+            Ok(user) if filter.is_some_and(|filter| !matching::filter(&user, filter)) => {}
+            Ok(user) => users.push(user),
+            Err(e) => errors.push(e),
+        }
+    }
+    (users, errors)
+}
+
+/// Finds Steam, reporting the failure.
+fn find_steam() -> Result<Steam, ExitCode> {
+    cli::steam::find().map_err(|e| {
+        error!("Failed to find Steam: {e}");
+        ExitCode::from(&e)
+    })
+}
+
+/// Restarts the given Steam per the given options and with the given arguments, and then launches the given app, if
+/// any.
+///
+/// Returns [`None`] in a dry run.
+fn restart_steam(
+    steam: &dyn SteamBackend,
+    options: RestartOptions,
+    launch_app: Option<u32>,
+    launch_args: &[OsString],
+    dry_run: DryRun,
+    stats: &mut Stats,
+) -> Option<ExitCode> {
+    let mut action = String::from("restart Steam");
+    if options.graceful {
+        action.push_str(" gracefully");
+    }
+    if options.silent {
+        action.push_str(" to the tray");
+    }
+    if !launch_args.is_empty() {
+        let args = launch_args
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>();
+        action.push_str(&format!(" with arguments {}", args.join(" ")));
+    }
+    if let Some(app_id) = launch_app {
+        action.push_str(&format!(" and launch app {app_id}"));
+    }
+    if options.forget_password {
+        action.push_str(", making it forget the password");
+    }
+    dry_run
+        .allows(action)
+        .then(|| cli::restart::restart(steam, options, launch_args, launch_app, stats))
+}
+
+/// Waits for Steam to log into the given user, reporting the result.
+fn confirm_login(steam: &dyn SteamBackend, username: Username, timeout: Duration) -> ExitCode {
+    let account_id = match loginusers::steam_id(steam, username) {
+        Ok(Some(steam_id)) => steam_id.account_id(),
+        Ok(None) => {
+            error!(
+                "Can't confirm the login: {username} isn't registered in the logged in users data."
+            );
+            return ExitCode::from(cli::restart::EXIT_UNCONFIRMED);
+        }
+        Err(e) => {
+            error!("Can't confirm the login: {e}");
+            return ExitCode::from(cli::restart::EXIT_UNCONFIRMED);
+        }
+    };
+
+    info!("⏳ waiting for Steam to log into {username}..");
+    match cli::restart::confirm(steam, account_id, timeout) {
+        Ok(()) => {
+            info!("✅ Steam logged into {username}");
+            ExitCode::SUCCESS
+        }
+        Err(Some(active)) => {
+            error!(
+                "Steam didn't log into {username} in time, it's logged into account ID {active}."
+            );
+            ExitCode::from(cli::restart::EXIT_UNCONFIRMED)
+        }
+        Err(None) => {
+            error!("Steam didn't log into {username} in time.");
+            ExitCode::from(cli::restart::EXIT_UNCONFIRMED)
+        }
+    }
+}
+
+/// Interactively picks a user to switch to.
+///
+/// Returns the picked username and whether it is the current auto-login user, or [`None`] if cancelled.
+fn pick_username(
+    steam: &dyn SteamBackend,
+    color: bool,
+) -> Result<Option<(Username, bool)>, ExitCode> {
+    let picked = loginusers::with_login_users(steam, |login_users| {
+        let users = login_users
+            .filter_map(|user| {
+                user.map_err(|e| error!("Failed to read user entry: {e}"))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        if users.is_empty() {
+            error!("No registered users to pick from.");
+            return Err(ExitCode::from(69));
+        }
+
+        let existing_username = steam.get_auto_login_user().ok();
+        let current = existing_username.and_then(|existing_username| {
+            users
+                .iter()
+                .position(|user| existing_username == *user.username)
+        });
+
+        let picked = cli::picker::pick(&users, current, color).map_err(|e| {
+            error!("Failed to read the selection: {e}");
+            ExitCode::from(74)
+        })?;
+        match picked {
+            Some(i) => match Username::try_from(users[i].username) {
+                Ok(username) => Ok(Some((username, Some(i) == current))),
+                Err(e) => {
+                    error!("Invalid username: {e}");
+                    Err(ExitCode::from(65))
                 }
             },
+            None => Ok(None),
+        }
+    });
+    picked.unwrap_or_else(|e| {
+        error!("Error: {e}");
+        Err(ExitCode::from(&e))
+    })
+}
+
+fn main() -> ExitCode {
+    cli::completions::complete(Cli::command);
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let color_arg =
+        (matches.value_source("color") == Some(ValueSource::CommandLine)).then_some(cli.color);
+    cli::output::set_quiet(cli.quiet);
+    cli::logger::init(cli.debug);
+    if let Some(steam_path) = cli.steam_path {
+        cli::steam::set_path(steam_path);
+    }
+    #[cfg(not(feature = "toml"))]
+    let config = Config::default();
+    #[cfg(feature = "toml")]
+    let config = if cli.no_config {
+        Config::default()
+    } else {
+        match Config::load() {
+            Ok(config) => config,
             Err(e) => {
-                eprintln!("Failed to find Steam: {e}");
+                error!("Error: {e}");
+                return ExitCode::from(78);
+            }
+        }
+    };
+    let porcelain = cli.porcelain;
+    let color = if porcelain {
+        ColorMode::Never
+    } else {
+        options::color(color_arg, &config)
+    };
+    let dry_run = DryRun(cli.dry_run);
+
+    match cli.command {
+        Command::Get(args) => {
+            let steam = cli::steam::LazySteam::default();
+            return get(&steam, args, porcelain, &mut io::stdout().lock());
+        }
+        Command::Set(args) => {
+            let steam = match find_steam() {
+                Ok(steam) => steam,
+                Err(code) => return code,
+            };
+            return set(&steam, args, &config, color, dry_run);
+        }
+        Command::Toggle { restart } => {
+            let options = RestartOptions::merge(restart, &config);
+            let previous = match cli::previous::load() {
+                Ok(Some(previous)) => previous,
+                Ok(None) => {
+                    info!("There's no previous account to switch back to yet, it's remembered when switching accounts with `set`.");
+                    return ExitCode::SUCCESS;
+                }
+                Err(e) => {
+                    error!("Error: {e}");
+                    return ExitCode::from(74);
+                }
+            };
+            let steam = match find_steam() {
+                Ok(steam) => steam,
+                Err(code) => return code,
+            };
+            if let Err(code) = switch(&steam, previous, true, dry_run) {
+                return code;
+            }
+            if !dry_run.0 {
+                info!("Switched back to {previous}.");
+            }
+            if options.restart {
+                if let Some(code) =
+                    restart_steam(&steam, options, None, &[], dry_run, &mut Stats::default())
+                {
+                    return code;
+                }
+            }
+        }
+        Command::Clear { restart } => {
+            let options = RestartOptions::merge(restart, &config);
+            let steam = cli::steam::LazySteam::default();
+            if dry_run.allows("clear the auto-login user") {
+                let previous = steam.get_auto_login_user().ok();
+                if let Err(e) = steam.clear_auto_login_user() {
+                    error!("Failed to clear the auto-login user: {e}");
+                    return ExitCode::from(&e);
+                }
+                info!("Cleared the auto-login user.");
+                if let Some(previous) = previous {
+                    if let Err(e) = cli::previous::save(previous) {
+                        warning!("failed to remember the previous account: {e}");
+                    }
+                }
+            }
+            if options.restart {
+                if let Some(code) =
+                    restart_steam(&steam, options, None, &[], dry_run, &mut Stats::default())
+                {
+                    return code;
+                }
+            }
+        }
+        Command::List(args) => {
+            let steam = cli::steam::LazySteam::default();
+            return list(
+                &steam,
+                args,
+                &config,
+                porcelain,
+                color,
+                &mut io::stdout().lock(),
+            );
+        }
+        Command::Launch {
+            verify,
+            force,
+            args,
+        } => {
+            if dry_run.allows("launch Steam") {
+                return cli::launch::launch(verify, force, &args);
+            }
+        }
+        Command::Open { url } => {
+            if dry_run.allows(format_args!("open {url}")) {
+                return cli::open::open(&url);
+            }
+        }
+        Command::Kill {
+            graceful,
+            shutdown_timeout,
+            then_kill,
+            with_helpers,
+        } => {
+            let action = if graceful {
+                "shut down Steam"
+            } else {
+                "kill Steam"
+            };
+            if dry_run.allows(action) {
+                let graceful = graceful.then(|| Duration::from_secs(shutdown_timeout));
+                return cli::kill::kill(graceful, then_kill, with_helpers);
+            }
+        }
+        Command::Status { json } => {
+            let status = cli::status::Status::gather();
+            if porcelain {
+                println!("{}", cli::porcelain::status(&status));
+            } else if json {
+                if let Err(e) = status.print_json() {
+                    error!("Failed to format the status as JSON: {e}");
+                    return ExitCode::from(70);
+                }
+            } else {
+                status.print_text();
+            }
+        }
+        Command::Rename { username, nickname } => {
+            let steam = match find_steam() {
+                Ok(steam) => steam,
+                Err(code) => return code,
+            };
+            let username = match resolve_username(&steam, &username, false, false, &config) {
+                Ok(username) => username,
+                Err(code) => return code,
+            };
+            match cli::rename::rename(username, &nickname, dry_run) {
+                Ok(()) if dry_run.0 => {}
+                Ok(()) => info!("Renamed {username} to {nickname}."),
+                Err(e) => {
+                    error!("Failed to rename {username}: {e}");
+                    return ExitCode::from(&e);
+                }
+            }
+        }
+        Command::Watch { format, poll } => {
+            if let Err(e) = cli::watch::watch(format, poll.map(Duration::from_millis)) {
+                error!("Error: {e}");
                 return ExitCode::from(&e);
             }
+        }
+        Command::Export { format } => return cli::archive::export(&config, format),
+        Command::Import { file, auto_login } => {
+            return cli::archive::import(&file, auto_login, config, dry_run)
+        }
+        Command::Doctor => return cli::doctor::doctor(),
+        #[cfg(windows)]
+        Command::Daemon => return daemon(&config, dry_run),
+        #[cfg(feature = "toml")]
+        Command::Alias { command } => return alias(command, config, dry_run),
+        Command::Completions { shell } => {
+            if let Err(e) = cli::completions::generate(shell, &Cli::command(), &mut io::stdout()) {
+                error!("Failed to print the completion script: {e}");
+                return ExitCode::from(74);
+            }
+        }
+        Command::DumpTokens { file } => {
+            let source = match fs::read(&file) {
+                Ok(source) => source,
+                Err(e) => {
+                    error!("Failed to read {}: {e}", file.display());
+                    return ExitCode::from(66);
+                }
+            };
+            match cli::dump::tokens(&source, &mut io::stdout().lock()) {
+                Ok(Ok(())) => {}
+                Ok(Err((offset, e))) => {
+                    error!("Lexing error at offset {offset}: {e}");
+                    return ExitCode::from(65);
+                }
+                Err(e) => {
+                    error!("Failed to write the tokens: {e}");
+                    return ExitCode::from(74);
+                }
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Runs `get`, printing the current account to `out`.
+fn get(steam: &dyn SteamBackend, args: GetArgs, porcelain: bool, out: &mut impl Write) -> ExitCode {
+    let username = match get_current(steam, args.source, args.verbose, porcelain, out) {
+        Ok(username) => username,
+        Err(code) => return code,
+    };
+    let line = match args.steamid {
+        Some(format) => {
+            let found = loginusers::with_user(steam, username, |user| {
+                user.and_then(|user| {
+                    Some((user.steam_id()?, cli::porcelain::get(username, Some(user))))
+                })
+            });
+            let (steam_id, record) = match found {
+                Ok(Some(found)) => found,
+                Ok(None) => {
+                    error!("Error: {username} isn't registered in the logged in users data, its SteamID is unknown.");
+                    return ExitCode::from(67);
+                }
+                Err(e) => {
+                    error!("Error: {e}");
+                    return ExitCode::from(&e);
+                }
+            };
+            match format {
+                _ if porcelain => record,
+                SteamIdFormat::Id64 => steam_id.to_string(),
+                SteamIdFormat::Steam2 => steam_id.steam2().to_string(),
+                SteamIdFormat::Steam3 => steam_id.steam3().to_string(),
+                SteamIdFormat::AccountId => steam_id.account_id().to_string(),
+            }
+        }
+        None if porcelain => {
+            let record =
+                loginusers::with_user(steam, username, |user| cli::porcelain::get(username, user));
+            record.unwrap_or_else(|_| cli::porcelain::get(username, None))
+        }
+        None if args.nickname || args.full => match loginusers::nickname(steam, username) {
+            Ok(Some(nickname)) if args.nickname => String::from_utf8_lossy(&nickname).into_owned(),
+            Ok(Some(nickname)) => format!("{username} ({})", String::from_utf8_lossy(&nickname)),
+            Ok(None) => {
+                warning!("{username} isn't registered in the logged in users data, its nickname is unknown.");
+                username.to_string()
+            }
+            Err(e) => {
+                warning!("{e}");
+                username.to_string()
+            }
         },
+        None => username.to_string(),
+    };
+    match writeln!(out, "{line}") {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("Failed to print the account: {e}");
+            ExitCode::from(74)
+        }
+    }
+}
+
+/// Runs `set` against the given Steam, printing the phase timings at the end with `--stats`, however it ends.
+fn set(
+    steam: &dyn SteamBackend,
+    args: SetArgs,
+    config: &Config,
+    color: ColorMode,
+    dry_run: DryRun,
+) -> ExitCode {
+    let print_stats = args.stats;
+    let mut stats = Stats::default();
+    let code = set_timed(steam, args, config, color, dry_run, &mut stats);
+    if print_stats {
+        stats.print();
     }
+    code
+}
 
+/// Runs `set` like [`set`], recording the duration of each phase in `stats`.
+fn set_timed(
+    steam: &dyn SteamBackend,
+    args: SetArgs,
+    config: &Config,
+    color: ColorMode,
+    dry_run: DryRun,
+    stats: &mut Stats,
+) -> ExitCode {
+    let SetArgs {
+        username,
+        stdin,
+        most_recent,
+        exact,
+        by_nickname,
+        restart,
+        force,
+        launch_app,
+        launch_args,
+        confirm,
+        confirm_timeout,
+        wait,
+        no_verify_write,
+        forget_password,
+        ..
+    } = args;
+    let options = RestartOptions {
+        forget_password,
+        ..RestartOptions::merge(restart, config)
+    };
+    let restart = options.restart
+        || launch_app.is_some()
+        || !launch_args.is_empty()
+        || confirm
+        || wait.is_some();
+    let username = if stdin {
+        match read_stdin_name() {
+            Ok(name) => Some(name),
+            Err(code) => return code,
+        }
+    } else {
+        username
+    };
+    let is_current = |username: Username| {
+        steam
+            .get_auto_login_user()
+            .is_ok_and(|current| current == username)
+    };
+    let (username, is_current) = match username {
+        _ if most_recent => match resolve_most_recent(steam) {
+            Ok(username) => (username, is_current(username)),
+            Err(code) => return code,
+        },
+        Some(name) => match resolve_username(steam, &name, exact, by_nickname, config) {
+            Ok(username) => (username, is_current(username)),
+            Err(code) => return code,
+        },
+        None if atty::is(atty::Stream::Stdin) => {
+            let should_color = options::should_color(color, atty::Stream::Stderr);
+            match pick_username(steam, should_color) {
+                Ok(Some(picked)) => picked,
+                Ok(None) => return ExitCode::SUCCESS,
+                Err(code) => return code,
+            }
+        }
+        None => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the USERNAME argument is required when not running interactively",
+            )
+            .exit(),
+    };
+    // with a restart, the restart makes Steam forget the password once it's stopped.
+    let restarts = restart && (!is_current || force);
+    if forget_password && !restarts && dry_run.allows("make Steam forget the password") {
+        if let Err(e) = steam.set_remember_password(false) {
+            error!("Failed to make Steam forget the password: {e}");
+            return ExitCode::from(&e);
+        }
+    }
+    if is_current {
+        info!("Already set to {username}.");
+        if !(restart && force) {
+            return ExitCode::SUCCESS;
+        }
+    } else if let Err(code) = stats.time("registry write", || {
+        switch(steam, username, !no_verify_write, dry_run)
+    }) {
+        return code;
+    }
+    if restart {
+        if let Some(code) = restart_steam(steam, options, launch_app, &launch_args, dry_run, stats)
+        {
+            let timeout = if confirm { Some(confirm_timeout) } else { wait };
+            return match timeout {
+                Some(timeout) if code == ExitCode::SUCCESS => stats.time("wait for login", || {
+                    confirm_login(steam, username, Duration::from_secs(timeout))
+                }),
+                _ => code,
+            };
+        }
+    }
     ExitCode::SUCCESS
 }
+
+/// Runs `list` against the given Steam, printing the users to `out`.
+fn list(
+    steam: &dyn SteamBackend,
+    args: ListArgs,
+    config: &Config,
+    porcelain: bool,
+    color: ColorMode,
+    out: &mut impl Write,
+) -> ExitCode {
+    let ListArgs {
+        filter,
+        verbose,
+        format,
+        sort,
+        reverse,
+        file,
+        count,
+    } = args;
+    if format != cli::list::Format::Text {
+        let users = with_listed_users(steam, file.as_deref(), |login_users, selected| {
+            let (users, errors) = collect_users(login_users, filter.as_deref());
+            cli::list::Users {
+                users: users
+                    .iter()
+                    .map(|&user| cli::list::User {
+                        selected: selected.is_some_and(|selected| selected == *user.username),
+                        ..cli::list::User::from(user)
+                    })
+                    .collect(),
+                errors: errors.iter().map(ToString::to_string).collect(),
+            }
+        });
+        let mut users = match users {
+            Ok(users) => users,
+            Err(e) => {
+                error!("Error: {e}");
+                return ExitCode::from(&e);
+            }
+        };
+        cli::sort::sort(&mut users.users, sort, reverse, |user| {
+            (
+                user.login_user.username.as_bytes(),
+                user.login_user.timestamp,
+            )
+        });
+        let printed = match format {
+            #[cfg(feature = "toml")]
+            cli::list::Format::Toml => {
+                for e in &users.errors {
+                    error!("Failed to read user entry: {e}");
+                }
+                match users.to_toml() {
+                    Ok(toml) => write!(out, "{toml}"),
+                    Err(e) => {
+                        error!("Failed to format the users as TOML: {e}");
+                        return ExitCode::from(70);
+                    }
+                }
+            }
+            _ => match serde_json::to_string_pretty(&users) {
+                Ok(json) => writeln!(out, "{json}"),
+                Err(e) => {
+                    error!("Failed to format the users as JSON: {e}");
+                    return ExitCode::from(70);
+                }
+            },
+        };
+        return match printed {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("Failed to print the users: {e}");
+                ExitCode::from(74)
+            }
+        };
+    }
+
+    let should_color = options::should_color(color, atty::Stream::Stdout);
+    let listed = with_listed_users(
+        steam,
+        file.as_deref(),
+        |login_users, existing_username| -> io::Result<usize> {
+            let existing_username = existing_username
+                .as_ref()
+                .map(|username| username.as_bytes());
+
+            let (mut users, errors) = collect_users(login_users, filter.as_deref());
+            for e in &errors {
+                error!("Failed to read user entry: {e}");
+            }
+            if count {
+                writeln!(out, "{}", users.len())?;
+                return Ok(errors.len());
+            }
+            cli::sort::sort(&mut users, sort, reverse, |user| {
+                (user.username, user.timestamp)
+            });
+            if verbose && users.is_empty() {
+                if let Some(filter) = &filter {
+                    info!("No users match {filter}.");
+                }
+            }
+
+            if porcelain {
+                for user in users {
+                    let selected = Some(user.username) == existing_username;
+                    writeln!(out, "{}", cli::porcelain::list_user(&user, selected))?;
+                }
+                return Ok(errors.len());
+            }
+
+            // each user's label and last login, the latter right-aligned in a column after the former.
+            let now = cli::time::now();
+            let lines = users
+                .iter()
+                .map(|user| {
+                    let selected = Some(user.username) == existing_username;
+                    let aliases = config.aliases_of(user.username).collect::<Vec<_>>();
+                    let label = format!(
+                        "{} {} ({}){}",
+                        if selected { "◼" } else { "◻" },
+                        user.username.escape_ascii(),
+                        user.nickname.escape_ascii(),
+                        if aliases.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" [{}]", aliases.join(", "))
+                        },
+                    );
+                    let last_login = user.timestamp.map_or_else(
+                        || "never".to_owned(),
+                        |timestamp| cli::time::relative(timestamp, now),
+                    );
+                    (label, last_login)
+                })
+                .collect::<Vec<_>>();
+            let label_width = lines
+                .iter()
+                .map(|(label, _)| label.chars().count())
+                .max()
+                .unwrap_or(0);
+            let last_login_width = lines
+                .iter()
+                .map(|(_, last_login)| last_login.len())
+                .max()
+                .unwrap_or(0);
+
+            for (user, (label, last_login)) in users.iter().zip(lines) {
+                let selected = Some(user.username) == existing_username;
+                writeln!(
+                    out,
+                    "{ansi_start}{label:<label_width$}  {last_login:>last_login_width$}{ansi_end}",
+                    ansi_start = if should_color && selected {
+                        "\u{1B}[32m"
+                    } else {
+                        ""
+                    },
+                    ansi_end = if should_color { "\u{1B}[0m" } else { "" },
+                )?;
+                if verbose {
+                    writeln!(out, "    SteamID:     {}", user.steamid.escape_ascii())?;
+                    writeln!(
+                        out,
+                        "    Last login:  {}",
+                        user.timestamp
+                            .and_then(cli::time::local_datetime)
+                            .as_deref()
+                            .unwrap_or("unknown")
+                    )?;
+                    writeln!(
+                        out,
+                        "    Most recent: {}",
+                        if user.most_recent { "yes" } else { "no" }
+                    )?;
+                    writeln!(
+                        out,
+                        "    Password:    {}",
+                        if user.remember_password {
+                            "remembered"
+                        } else {
+                            "not remembered"
+                        }
+                    )?;
+                }
+            }
+            Ok(errors.len())
+        },
+    );
+    match listed {
+        Ok(Ok(malformed)) if count && malformed > 0 => ExitCode::from(65),
+        Ok(Ok(_)) => ExitCode::SUCCESS,
+        Ok(Err(e)) => {
+            error!("Failed to print the users: {e}");
+            ExitCode::from(74)
+        }
+        Err(e) => {
+            error!("Error: {e}");
+            ExitCode::from(&e)
+        }
+    }
+}
+
+/// Runs the hotkey daemon per the configuration.
+#[cfg(windows)]
+fn daemon(config: &Config, dry_run: DryRun) -> ExitCode {
+    if config.hotkeys.is_empty() {
+        error!("No hotkeys are configured, add them to the hotkeys table of the configuration file, e.g. \"Ctrl+Alt+1\" = \"my_account\".");
+        return ExitCode::from(78);
+    }
+    let mut hotkeys = BTreeMap::new();
+    for (text, name) in &config.hotkeys {
+        let hotkey = match cli::daemon::Hotkey::parse(text) {
+            Ok(hotkey) => hotkey,
+            Err(e) => {
+                error!("Error: {e}");
+                return ExitCode::from(78);
+            }
+        };
+        let username = match config.resolve(name) {
+            Ok(username) => username,
+            Err(e) => {
+                error!("Error: invalid username {name} for the hotkey {text}: {e}");
+                return ExitCode::from(78);
+            }
+        };
+        hotkeys.insert(text.clone(), (hotkey, username));
+    }
+
+    let options = RestartOptions {
+        restart: true,
+        graceful: true,
+        ..RestartOptions::merge(RestartArgs::default(), config)
+    };
+    let steam = match find_steam() {
+        Ok(steam) => steam,
+        Err(code) => return code,
+    };
+    let result = cli::daemon::run(&hotkeys, |username| {
+        if steam
+            .get_auto_login_user()
+            .is_ok_and(|current| current == username)
+        {
+            info!("Already set to {username}.");
+            return;
+        }
+        info!("Switching to {username}..");
+        if switch(&steam, username, true, dry_run).is_ok() {
+            restart_steam(&steam, options, None, &[], dry_run, &mut Stats::default());
+        }
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("Error: {e}");
+            ExitCode::from(69)
+        }
+    }
+}
+
+/// Runs an [`AliasCommand`].
+#[cfg(feature = "toml")]
+fn alias(command: AliasCommand, mut config: Config, dry_run: DryRun) -> ExitCode {
+    match command {
+        AliasCommand::Add { alias, username } => {
+            let collides = loginusers::find_steam().and_then(|steam| {
+                loginusers::with_login_users(&steam, |login_users| {
+                    login_users
+                        .filter_map(Result::ok)
+                        .any(|user| user.username.eq_ignore_ascii_case(alias.as_bytes()))
+                })
+            });
+            if let Ok(true) = collides {
+                warning!("the alias {alias} is also the username of a registered user, the alias will take precedence.");
+            }
+            config.aliases.insert(alias, username.to_string());
+        }
+        AliasCommand::Remove { alias } => {
+            if config.aliases.remove(&alias).is_none() {
+                error!("No such alias: {alias}");
+                return ExitCode::from(65);
+            }
+        }
+        AliasCommand::List => {
+            for (alias, username) in &config.aliases {
+                println!("{alias} → {username}");
+            }
+            return ExitCode::SUCCESS;
+        }
+    }
+
+    if !dry_run.allows("save the aliases to the configuration file") {
+        return ExitCode::SUCCESS;
+    }
+    match config.save() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("Failed to save the aliases: {e}");
+            ExitCode::from(73)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use diverter::mock::MockSteam;
+
+    use super::*;
+
+    const LOGINUSERS: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/vdf/fixtures/loginusers.vdf"
+    );
+
+    fn username(name: &str) -> Username {
+        Username::try_from(name).unwrap()
+    }
+
+    /// Parses the command line after `diverter`.
+    fn command(args: &[&str]) -> Command {
+        Cli::try_parse_from(std::iter::once("diverter").chain(args.iter().copied()))
+            .unwrap()
+            .command
+    }
+
+    /// Runs `get` with the given arguments, returning its exit code and output.
+    fn run_get(steam: &MockSteam, args: &[&str], porcelain: bool) -> (ExitCode, String) {
+        let Command::Get(args) = command(&[&["get"], args].concat()) else {
+            unreachable!()
+        };
+        let mut out = Vec::new();
+        let code = get(steam, args, porcelain, &mut out);
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    /// Runs `list` with the given arguments, returning its exit code and output.
+    fn run_list(steam: &MockSteam, args: &[&str], porcelain: bool) -> (ExitCode, String) {
+        let Command::List(args) = command(&[&["list"], args].concat()) else {
+            unreachable!()
+        };
+        let mut out = Vec::new();
+        let code = list(
+            steam,
+            args,
+            &Config::default(),
+            porcelain,
+            ColorMode::Never,
+            &mut out,
+        );
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    /// Runs `set` with the given arguments.
+    fn run_set(steam: &MockSteam, args: &[&str], dry_run: bool) -> ExitCode {
+        let Command::Set(args) = command(&[&["set"], args].concat()) else {
+            unreachable!()
+        };
+        set(
+            steam,
+            args,
+            &Config::default(),
+            ColorMode::Never,
+            DryRun(dry_run),
+        )
+    }
+
+    #[test]
+    fn get_registry() {
+        let steam = MockSteam::new()
+            .with_auto_login_user(Some(username("bob")))
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_get(&steam, &[], false),
+            (ExitCode::SUCCESS, "bob\n".into())
+        );
+        assert_eq!(
+            run_get(&steam, &["--full"], false),
+            (ExitCode::SUCCESS, "bob (Bob)\n".into())
+        );
+        assert_eq!(
+            run_get(&steam, &["--nickname"], false),
+            (ExitCode::SUCCESS, "Bob\n".into())
+        );
+        assert_eq!(
+            run_get(&steam, &["--steamid", "steam3"], false),
+            (ExitCode::SUCCESS, "[U:1:22203]\n".into())
+        );
+        assert_eq!(
+            run_get(&steam, &[], true),
+            (ExitCode::SUCCESS, "bob\tBob\t76561197960287931\n".into())
+        );
+    }
+
+    #[test]
+    fn get_invalid() {
+        let steam = MockSteam::new().with_auto_login_user_raw(Some(b"bad name\xFF".to_vec()));
+        assert_eq!(
+            run_get(&steam, &[], false),
+            (ExitCode::from(78), "bad name\\xff\n".into())
+        );
+        assert_eq!(
+            run_get(&steam, &[], true),
+            (ExitCode::from(78), String::new())
+        );
+    }
+
+    #[test]
+    fn get_none() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_get(&steam, &[], false),
+            (ExitCode::from(EXIT_NO_CURRENT), String::new())
+        );
+    }
+
+    #[test]
+    fn get_vdf() {
+        let steam = MockSteam::new()
+            .with_auto_login_user(Some(username("alice")))
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_get(&steam, &["--source", "vdf"], false),
+            (ExitCode::SUCCESS, "bob\n".into())
+        );
+    }
+
+    #[test]
+    fn get_unregistered_steamid() {
+        let steam = MockSteam::new()
+            .with_auto_login_user(Some(username("dave")))
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_get(&steam, &["--steamid", "64"], false),
+            (ExitCode::from(67), String::new())
+        );
+    }
+
+    #[test]
+    fn list_porcelain() {
+        let steam = MockSteam::new()
+            .with_auto_login_user(Some(username("alice")))
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_list(&steam, &["--sort", "name", "--reverse"], true),
+            (
+                ExitCode::SUCCESS,
+                "0\tcarol\tCarol \\\"C\\\"\t76561197960287932\t\t0\t0\n\
+                 0\tbob\tBob\t76561197960287931\t1700050000\t1\t1\n\
+                 1\talice\tAlice\t76561197960287930\t1700000000\t0\t1\n"
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn list_count() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_list(&steam, &["--count"], false),
+            (ExitCode::SUCCESS, "3\n".into())
+        );
+        assert_eq!(
+            run_list(&steam, &["--count", "--filter", "O"], false),
+            (ExitCode::SUCCESS, "2\n".into())
+        );
+    }
+
+    #[test]
+    fn list_json() {
+        let steam = MockSteam::new()
+            .with_auto_login_user(Some(username("bob")))
+            .with_loginusers(LOGINUSERS);
+        let (code, json) = run_list(&steam, &["--format", "json"], false);
+        assert_eq!(code, ExitCode::SUCCESS);
+        let users = serde_json::from_str::<cli::list::Users>(&json).unwrap();
+        let selected = users
+            .users
+            .iter()
+            .filter(|user| user.selected)
+            .map(|user| user.login_user.username.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(users.users.len(), 3);
+        assert_eq!(selected, ["bob"]);
+    }
+
+    #[test]
+    fn list_without_loginusers() {
+        let steam = MockSteam::new();
+        assert_eq!(
+            run_list(&steam, &[], false),
+            (ExitCode::from(69), String::new())
+        );
+    }
+
+    #[test]
+    fn set_matches() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(run_set(&steam, &["CAR"], false), ExitCode::SUCCESS);
+        assert_eq!(steam.auto_login_user(), Some(username("carol")));
+    }
+
+    #[test]
+    fn set_by_nickname() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(&steam, &["--by-nickname", "alice"], false),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(steam.auto_login_user(), Some(username("alice")));
+    }
+
+    #[test]
+    fn set_most_recent() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(&steam, &["--most-recent"], false),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(steam.auto_login_user(), Some(username("bob")));
+    }
+
+    #[test]
+    fn most_recent_ambiguous() {
+        // alice is marked along with bob.
+        let source = fs::read_to_string(LOGINUSERS).unwrap().replacen(
+            "\"MostRecent\"\t\t\"0\"",
+            "\"MostRecent\"\t\t\"1\"",
+            1,
+        );
+        let path = std::env::temp_dir().join("diverter-most-recent-ambiguous.vdf");
+        fs::write(&path, source).unwrap();
+        let steam = MockSteam::new().with_loginusers(&path);
+        assert_eq!(
+            run_get(&steam, &["--source", "vdf"], false),
+            (ExitCode::from(65), String::new())
+        );
+        assert_eq!(
+            run_set(&steam, &["--most-recent"], false),
+            ExitCode::from(65)
+        );
+        assert_eq!(steam.auto_login_user(), None);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn set_unregistered() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(run_set(&steam, &["dave"], false), ExitCode::from(67));
+        assert_eq!(steam.auto_login_user(), None);
+        assert_eq!(
+            run_set(&steam, &["--exact", "dave"], false),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(steam.auto_login_user(), Some(username("dave")));
+    }
+
+    #[test]
+    fn set_current() {
+        // setting is ignored, so a write would fail verification.
+        let steam = MockSteam::new()
+            .with_auto_login_user(Some(username("bob")))
+            .with_set_ignored(true)
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(run_set(&steam, &["bob"], false), ExitCode::SUCCESS);
+    }
+
+    #[test]
+    fn set_dry_run() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(run_set(&steam, &["bob"], true), ExitCode::SUCCESS);
+        assert_eq!(steam.auto_login_user(), None);
+    }
+
+    #[test]
+    fn set_restart() {
+        let steam = MockSteam::new()
+            .with_running(true)
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(
+                &steam,
+                &["--restart", "--launch-arg", "-console", "bob"],
+                false
+            ),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(steam.auto_login_user(), Some(username("bob")));
+        assert!(steam.is_running().unwrap());
+        assert_eq!(steam.launches().len(), 1);
+        assert_eq!(steam.launches()[0].last().unwrap(), "-console");
+        assert_eq!(steam.remember_password(), None);
+    }
+
+    #[test]
+    fn set_restart_dry_run() {
+        let steam = MockSteam::new()
+            .with_running(true)
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(&steam, &["--restart", "--forget-password", "bob"], true),
+            ExitCode::SUCCESS
+        );
+        assert!(steam.launches().is_empty());
+        assert_eq!(steam.remember_password(), None);
+    }
+
+    #[test]
+    fn set_confirm() {
+        // bob's account ID.
+        let steam = MockSteam::new()
+            .with_active_user(Some(22203))
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(&steam, &["--confirm", "bob"], false),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(steam.launches().len(), 1);
+
+        let steam = MockSteam::new()
+            .with_active_user(Some(22202))
+            .with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(
+                &steam,
+                &["--confirm", "--confirm-timeout", "0", "bob"],
+                false
+            ),
+            ExitCode::from(cli::restart::EXIT_UNCONFIRMED)
+        );
+    }
+
+    #[test]
+    fn set_forget_password() {
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(&steam, &["--forget-password", "bob"], false),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(steam.remember_password(), Some(false));
+        assert!(steam.launches().is_empty());
+
+        let steam = MockSteam::new().with_loginusers(LOGINUSERS);
+        assert_eq!(
+            run_set(&steam, &["--restart", "--forget-password", "bob"], false),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(steam.remember_password(), Some(false));
+        assert_eq!(steam.launches().len(), 1);
+    }
+
+    #[test]
+    fn switch_mismatch() {
+        let steam = MockSteam::new()
+            .with_auto_login_user(Some(username("alice")))
+            .with_set_ignored(true);
+        assert_eq!(
+            switch(&steam, username("bob"), true, DryRun(false)),
+            Err(ExitCode::from(71))
+        );
+        assert_eq!(steam.auto_login_user(), Some(username("alice")));
+    }
+
+    #[test]
+    fn switch_no_verify_write() {
+        let steam = MockSteam::new().with_set_ignored(true);
+        assert_eq!(
+            switch(&steam, username("bob"), false, DryRun(false)),
+            Ok(())
+        );
+        assert_eq!(steam.auto_login_user(), None);
+    }
+
+    #[test]
+    fn switch_verified() {
+        let steam = MockSteam::new();
+        assert_eq!(switch(&steam, username("bob"), true, DryRun(false)), Ok(()));
+        assert_eq!(steam.auto_login_user(), Some(username("bob")));
+    }
+}
@@ -0,0 +1,212 @@
+//! An in-memory [`SteamBackend`], for exercising code that drives Steam without a Steam installation.
+
+use std::{
+    cell::{Cell, RefCell},
+    ffi::{OsStr, OsString},
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, Result, SteamBackend, Username};
+
+/// A [`SteamBackend`] test double that keeps the auto-login user and whether Steam is running in memory, and reads
+/// the logged in users from a given file.
+///
+/// Launching, shutting down and killing it just flip whether it's running, though it can be set to
+/// [ignore shutting down](Self::with_shutdown_ignored), and to [ignore setting](Self::with_set_ignored) the
+/// auto-login user. Launches are recorded with their arguments, see [`Self::launches`].
+///
+/// # Example
+///
+/// ```
+/// use diverter::{mock::MockSteam, SteamBackend, Username};
+///
+/// let steam = MockSteam::new().with_running(true);
+/// let username = Username::try_from("my_account").unwrap();
+/// steam.set_auto_login_user(username).unwrap();
+/// assert_eq!(steam.get_auto_login_user().unwrap(), username);
+/// assert_eq!(steam.kill().unwrap(), 1);
+/// assert!(!steam.is_running().unwrap());
+/// ```
+#[derive(Debug, Default)]
+pub struct MockSteam {
+    auto_login_user: RefCell<Option<Vec<u8>>>,
+    running: Cell<bool>,
+    launches: RefCell<Vec<Vec<OsString>>>,
+    remember_password: Cell<Option<bool>>,
+    active_user: Cell<Option<u32>>,
+    shutdown_ignored: bool,
+    set_ignored: bool,
+    loginusers: Option<PathBuf>,
+}
+
+impl MockSteam {
+    /// Creates a stopped [`MockSteam`] with no auto-login user and no logged in users data.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the initial auto-login user.
+    #[inline]
+    pub fn with_auto_login_user(self, username: Option<Username>) -> Self {
+        self.with_auto_login_user_raw(username.map(|username| username.as_bytes().to_vec()))
+    }
+
+    /// Sets the initial auto-login user's value as is, e.g. to one that isn't a valid [`Username`].
+    #[inline]
+    pub fn with_auto_login_user_raw(self, value: Option<Vec<u8>>) -> Self {
+        self.auto_login_user
+            .replace(value.filter(|value| !value.is_empty()));
+        self
+    }
+
+    /// Sets whether Steam is initially running.
+    #[inline]
+    pub fn with_running(self, running: bool) -> Self {
+        self.running.set(running);
+        self
+    }
+
+    /// Sets the account ID of the user Steam is logged into.
+    #[inline]
+    pub fn with_active_user(self, account_id: Option<u32>) -> Self {
+        self.active_user.set(account_id);
+        self
+    }
+
+    /// Sets whether shutting down leaves it running, as when Steam hangs on exit.
+    #[inline]
+    pub fn with_shutdown_ignored(self, ignored: bool) -> Self {
+        Self {
+            shutdown_ignored: ignored,
+            ..self
+        }
+    }
+
+    /// Sets whether setting the auto-login user leaves the previous one in place, as when another program overwrites
+    /// it right away.
+    #[inline]
+    pub fn with_set_ignored(self, ignored: bool) -> Self {
+        Self {
+            set_ignored: ignored,
+            ..self
+        }
+    }
+
+    /// Sets the loginusers.vdf file to serve from [`SteamBackend::vdf_loginusers`].
+    #[inline]
+    pub fn with_loginusers(self, path: impl AsRef<Path>) -> Self {
+        Self {
+            loginusers: Some(path.as_ref().to_owned()),
+            ..self
+        }
+    }
+
+    /// Gets the current auto-login user, or [`None`] if there's none, or it isn't a valid [`Username`].
+    #[inline]
+    pub fn auto_login_user(&self) -> Option<Username> {
+        self.get_auto_login_user().ok()
+    }
+
+    /// Gets the arguments of each launch so far, in order.
+    #[inline]
+    pub fn launches(&self) -> Vec<Vec<OsString>> {
+        self.launches.borrow().clone()
+    }
+
+    /// Gets whether Steam remembers the password, as last set, or [`None`] if it was never set.
+    #[inline]
+    pub fn remember_password(&self) -> Option<bool> {
+        self.remember_password.get()
+    }
+}
+
+impl MockSteam {
+    /// The process ID it reports while running.
+    pub const PID: u32 = 1;
+}
+
+impl SteamBackend for MockSteam {
+    fn get_auto_login_user(&self) -> Result<Username> {
+        let value = self.get_auto_login_user_raw()?;
+        Username::try_from(value.as_slice()).map_err(Error::InvalidUsernameInRegistry)
+    }
+
+    fn get_auto_login_user_raw(&self) -> Result<Vec<u8>> {
+        self.auto_login_user
+            .borrow()
+            .clone()
+            .ok_or_else(|| Error::ReadSteamRegistry(io::Error::from(io::ErrorKind::NotFound)))
+    }
+
+    fn set_auto_login_user(&self, username: Username) -> Result<()> {
+        if !self.set_ignored {
+            self.auto_login_user
+                .replace(Some(username.as_bytes().to_vec()));
+        }
+        Ok(())
+    }
+
+    fn clear_auto_login_user(&self) -> Result<()> {
+        self.auto_login_user.replace(None);
+        Ok(())
+    }
+
+    fn vdf_loginusers(&self) -> Result<File> {
+        match &self.loginusers {
+            Some(path) => File::open(path).map_err(Error::VdfOpen),
+            None => Err(Error::VdfOpen(io::Error::from(io::ErrorKind::NotFound))),
+        }
+    }
+
+    fn launch(&self) -> Result<()> {
+        self.launch_with_args(&[])
+    }
+
+    fn launch_with_args(&self, args: &[&OsStr]) -> Result<()> {
+        self.launches
+            .borrow_mut()
+            .push(args.iter().map(|&arg| arg.to_owned()).collect());
+        self.running.set(true);
+        Ok(())
+    }
+
+    fn set_remember_password(&self, remember: bool) -> Result<()> {
+        self.remember_password.set(Some(remember));
+        Ok(())
+    }
+
+    fn get_active_user(&self) -> Result<Option<u32>> {
+        Ok(self.active_user.get())
+    }
+
+    fn kill(&self) -> Result<usize> {
+        Ok(usize::from(self.running.replace(false)))
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        if !self.shutdown_ignored {
+            self.running.set(false);
+        }
+        Ok(())
+    }
+
+    fn is_running(&self) -> Result<bool> {
+        Ok(self.running.get())
+    }
+
+    fn pid(&self) -> Result<Option<u32>> {
+        Ok(self.running.get().then_some(Self::PID))
+    }
+
+    /// Fails with [`Error::ReadVersion`], as there's no installed client to read the version of.
+    fn client_version(&self) -> Result<String> {
+        Err(Error::ReadVersion(io::Error::from(io::ErrorKind::NotFound)))
+    }
+
+    fn check_registry_access(&self) -> Result<()> {
+        Ok(())
+    }
+}
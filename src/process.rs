@@ -0,0 +1,163 @@
+//! Process enumeration for finding and terminating Steam's processes.
+
+use std::{ffi::OsString, io, mem, os::windows::ffi::OsStringExt, time::Duration};
+
+use winapi::{
+    shared::{
+        minwindef::{DWORD, FALSE, MAX_PATH},
+        winerror::WAIT_TIMEOUT,
+    },
+    um::{
+        handleapi::{CloseHandle, INVALID_HANDLE_VALUE},
+        processthreadsapi::{OpenProcess, TerminateProcess},
+        synchapi::WaitForSingleObject,
+        tlhelp32::{
+            CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+            TH32CS_SNAPPROCESS,
+        },
+        winbase::{QueryFullProcessImageNameW, WAIT_OBJECT_0},
+        winnt::{HANDLE, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE, SYNCHRONIZE},
+    },
+};
+
+/// An owned handle, closed on drop.
+struct Handle(HANDLE);
+
+impl Drop for Handle {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: the handle is owned and valid.
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+/// A running process.
+pub(crate) struct Process {
+    /// The process ID.
+    pub pid: u32,
+    /// The lowercase path to the process' executable.
+    pub path: String,
+    /// A handle to the process with query, terminate and synchronize access.
+    handle: Handle,
+}
+
+impl Process {
+    /// Gets the lowercase file name of the process' executable.
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('\\').next().unwrap_or(&self.path)
+    }
+
+    /// Terminates the process.
+    pub fn terminate(&self) -> io::Result<()> {
+        // SAFETY: the handle is valid and has terminate access.
+        if unsafe { TerminateProcess(self.handle.0, 0) } != FALSE {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    /// Waits up to the given timeout for the process to exit.
+    ///
+    /// Returns whether it exited.
+    pub fn wait(&self, timeout: Duration) -> io::Result<bool> {
+        let timeout_ms = timeout.as_millis().min(DWORD::MAX as u128 - 1) as DWORD;
+        // SAFETY: the handle is valid and has synchronize access.
+        match unsafe { WaitForSingleObject(self.handle.0, timeout_ms) } {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+/// Opens the process of the given ID if its executable is within the given lowercase directory path.
+fn open_in_dir(pid: DWORD, dir: &str) -> Option<Process> {
+    // SAFETY: no preconditions.
+    let handle = unsafe {
+        OpenProcess(
+            PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_TERMINATE | SYNCHRONIZE,
+            FALSE,
+            pid,
+        )
+    };
+    if handle.is_null() {
+        return None;
+    }
+    let handle = Handle(handle);
+
+    let mut path = [0u16; MAX_PATH];
+    let mut path_len = path.len() as DWORD;
+    // SAFETY: the buffer's length is passed along.
+    if unsafe { QueryFullProcessImageNameW(handle.0, 0, path.as_mut_ptr(), &mut path_len) } == FALSE
+    {
+        return None;
+    }
+    let path = OsString::from_wide(&path[..path_len as usize])
+        .to_string_lossy()
+        .to_lowercase();
+    path.starts_with(dir)
+        .then_some(Process { pid, path, handle })
+}
+
+/// Finds a process that runs an executable of one of the given lowercase file names, returning its file name and
+/// PID.
+pub(crate) fn find_named(names: &[&str]) -> io::Result<Option<(String, u32)>> {
+    // SAFETY: no preconditions.
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+    let snapshot = Handle(snapshot);
+
+    // SAFETY: PROCESSENTRY32W is plain data.
+    let mut entry: PROCESSENTRY32W = unsafe { mem::zeroed() };
+    entry.dwSize = mem::size_of::<PROCESSENTRY32W>() as DWORD;
+    // SAFETY: the entry's size is set.
+    let mut has_entry = unsafe { Process32FirstW(snapshot.0, &mut entry) } != FALSE;
+    while has_entry {
+        let len = entry
+            .szExeFile
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(entry.szExeFile.len());
+        let name = OsString::from_wide(&entry.szExeFile[..len])
+            .to_string_lossy()
+            .to_lowercase();
+        if names.contains(&name.as_str()) {
+            log::trace!("found {name} (PID {})", entry.th32ProcessID);
+            return Ok(Some((name, entry.th32ProcessID)));
+        }
+        // SAFETY: the entry's size is set.
+        has_entry = unsafe { Process32NextW(snapshot.0, &mut entry) } != FALSE;
+    }
+    Ok(None)
+}
+
+/// Lists the processes whose executables are within the given lowercase directory path.
+///
+/// Processes that can't be opened (e.g. due to insufficient permissions) are skipped.
+pub(crate) fn in_dir(dir: &str) -> io::Result<Vec<Process>> {
+    // SAFETY: no preconditions.
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+    let snapshot = Handle(snapshot);
+
+    // SAFETY: PROCESSENTRY32W is plain data.
+    let mut entry: PROCESSENTRY32W = unsafe { mem::zeroed() };
+    entry.dwSize = mem::size_of::<PROCESSENTRY32W>() as DWORD;
+    let mut processes = Vec::new();
+    // SAFETY: the entry's size is set.
+    let mut has_entry = unsafe { Process32FirstW(snapshot.0, &mut entry) } != FALSE;
+    while has_entry {
+        if let Some(process) = open_in_dir(entry.th32ProcessID, dir) {
+            log::trace!("found {} (PID {})", process.path, process.pid);
+            processes.push(process);
+        }
+        // SAFETY: the entry's size is set.
+        has_entry = unsafe { Process32NextW(snapshot.0, &mut entry) } != FALSE;
+    }
+    Ok(processes)
+}
@@ -1,22 +1,32 @@
 //! Steam client operations.
 
 use std::{
-    ffi::c_char,
+    ffi::{c_char, c_void, OsStr, OsString},
     fmt::Debug,
     fs::File,
-    io,
+    io::{self, Read},
     mem::MaybeUninit,
-    os::windows::prelude::{FromRawHandle, OsStringExt, RawHandle},
+    os::windows::prelude::{FromRawHandle, OsStrExt, OsStringExt, RawHandle},
+    path::{Path, PathBuf},
     process::ExitCode,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use winapi::{
     ctypes::wchar_t,
-    shared::minwindef::{DWORD, MAX_PATH},
+    shared::{
+        minwindef::{DWORD, HKEY, MAX_PATH},
+        winerror::ERROR_MORE_DATA,
+    },
 };
 
-use crate::{Username, UsernameError};
+pub mod registry;
+
+use crate::{
+    process::{self, Process},
+    vdf::{self, LoginUserVdfError, ScanParseError},
+    Username, UsernameError,
+};
 
 #[repr(C)]
 #[derive(Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -36,6 +46,14 @@ impl Debug for Steam {
     }
 }
 
+/// A watch for changes to the values in Steam's registry subkey, such as the auto-login user.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RegistryWatch {
+    key: HKEY,
+    event: *mut c_void,
+}
+
 /// Reflects `windows.c`'s `phase_t`.
 #[repr(C)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -49,6 +67,9 @@ enum CPhase {
     EnumProcesses,
     KillSteam,
     FileOpenVdf,
+    ReadVersion,
+    WatchSteamRegistry,
+    FindSteam,
 }
 
 /// Reflects `windows.c`'s `result_t`.
@@ -87,6 +108,36 @@ pub enum Error {
     /// Indicates failure to open a VDF file.
     #[error("failed to open a VDF file: {0}")]
     VdfOpen(io::Error),
+    /// Indicates failure to read the Steam client's version.
+    #[error("failed to read the Steam client's version: {0}")]
+    ReadVersion(io::Error),
+    /// Indicates failure to read a VDF file.
+    #[error("failed to read a VDF file: {0}")]
+    VdfRead(io::Error),
+    /// Indicates failure to parse a VDF file.
+    #[error("failed to parse a VDF file: {0}")]
+    VdfParse(ScanParseError),
+    /// Indicates libraryfolders.vdf doesn't list library folders.
+    #[error("missing expected \"libraryfolders\" subkeys in libraryfolders.vdf")]
+    VdfLibraryFolders,
+    /// Indicates failure to read the users out of loginusers.vdf.
+    #[error("failed to read the logged in users: {0}")]
+    VdfLoginUsers(LoginUserVdfError),
+    /// Indicates the user isn't registered in loginusers.vdf, or has no valid SteamID there.
+    #[error("the user {0} has no SteamID in the logged in users data")]
+    SteamIdNotFound(Username),
+    /// Indicates failure to watch Steam's registry subkey for changes.
+    #[error("failed to watch Steam's registry subkey for changes: {0}")]
+    WatchSteamRegistry(io::Error),
+    /// Indicates failure to find Steam's executable in a given directory.
+    #[error("failed to find steam.exe in the given directory: {0}")]
+    FindSteam(io::Error),
+    /// Indicates the auto-login user read back after setting it isn't the one that was set, e.g. because another
+    /// process rewrote it.
+    ///
+    /// Holds the user that was read back.
+    #[error("the auto-login user reads back as {0} after setting it, another process may have changed it")]
+    AutoLoginUserMismatch(Username),
 }
 
 /// Exit codes per `sysexits.h`.
@@ -94,6 +145,9 @@ impl<'a> From<&'a Error> for ExitCode {
     fn from(e: &'a Error) -> Self {
         ExitCode::from(match e {
             Error::InvalidUsernameInRegistry(_) => 78,
+            Error::VdfParse(_) | Error::VdfLibraryFolders | Error::VdfLoginUsers(_) => 65,
+            Error::SteamIdNotFound(_) => 67,
+            Error::AutoLoginUserMismatch(_) => 71,
             _ => 69,
         })
     }
@@ -102,6 +156,13 @@ impl<'a> From<&'a Error> for ExitCode {
 impl From<CResult> for Option<Error> {
     #[inline]
     fn from(value: CResult) -> Self {
+        if value.phase != CPhase::Ok {
+            log::debug!(
+                "{:?} failed with Win32 error code {}",
+                value.phase,
+                value.win_code
+            );
+        }
         match value.phase {
             CPhase::Ok => None,
             CPhase::ReadSteamRegistry => Some(Error::ReadSteamRegistry(
@@ -125,6 +186,15 @@ impl From<CResult> for Option<Error> {
             CPhase::FileOpenVdf => Some(Error::VdfOpen(io::Error::from_raw_os_error(
                 value.win_code as _,
             ))),
+            CPhase::ReadVersion => Some(Error::ReadVersion(io::Error::from_raw_os_error(
+                value.win_code as _,
+            ))),
+            CPhase::WatchSteamRegistry => Some(Error::WatchSteamRegistry(
+                io::Error::from_raw_os_error(value.win_code as _),
+            )),
+            CPhase::FindSteam => Some(Error::FindSteam(io::Error::from_raw_os_error(
+                value.win_code as _,
+            ))),
         }
     }
 }
@@ -132,14 +202,65 @@ impl From<CResult> for Option<Error> {
 #[link(name = "windowsutil")]
 extern "C" {
     fn steam_init(steam: *mut Steam) -> CResult;
+    fn steam_init_path(steam: *mut Steam, path: *const wchar_t, len: usize) -> CResult;
     fn steam_shutdown(steam: *const Steam) -> CResult;
     fn steam_launch(steam: *const Steam) -> CResult;
     fn steam_launch_fast(steam: *const Steam) -> CResult;
-    fn steam_kill(steam: *const Steam, killed: *mut u8) -> CResult;
+    fn steam_launch_with_args(steam: *const Steam, args: *mut wchar_t) -> CResult;
     fn steam_set_auto_login_user(username: *const c_char, username_len: usize) -> CResult;
+    fn steam_clear_auto_login_user() -> CResult;
     fn steam_get_auto_login_user(username: *mut c_char, username_len: *mut usize) -> CResult;
-    fn steam_is_running(steam: *const Steam, is_running: *mut u8) -> CResult;
+    fn steam_get_auto_login_user_raw(data: *mut c_char, size: *mut DWORD) -> CResult;
+    fn steam_get_active_user(account_id: *mut u32) -> CResult;
     fn steam_vdf_loginusers(steam: *const Steam, file: *mut RawHandle) -> CResult;
+    fn steam_client_version(steam: *const Steam, version: *mut u32) -> CResult;
+    fn steam_registry_check_access() -> CResult;
+    fn steam_registry_get_string(
+        name: *const wchar_t,
+        data: *mut wchar_t,
+        size: *mut DWORD,
+    ) -> CResult;
+    fn steam_registry_get_dword(name: *const wchar_t, value: *mut DWORD) -> CResult;
+    fn steam_registry_set_dword(name: *const wchar_t, value: DWORD) -> CResult;
+    fn steam_registry_set_string(
+        name: *const wchar_t,
+        data: *const wchar_t,
+        size: DWORD,
+    ) -> CResult;
+    fn steam_registry_watch_init(watch: *mut RegistryWatch) -> CResult;
+    fn steam_registry_watch_arm(watch: *const RegistryWatch) -> CResult;
+    fn steam_registry_watch_wait(
+        watch: *const RegistryWatch,
+        timeout_ms: DWORD,
+        changed: *mut u8,
+    ) -> CResult;
+    fn steam_registry_watch_close(watch: *mut RegistryWatch);
+}
+
+/// Executables of Steam's helper processes, such as the Steam Client Service, which aren't part of the client.
+const HELPERS: &[&str] = &["steamservice.exe"];
+
+/// Checks whether a Steam process of the given executable file name should be killed, which [helpers](HELPERS) are
+/// only if `with_helpers` is set.
+fn should_kill(file_name: &str, with_helpers: bool) -> bool {
+    with_helpers || !HELPERS.contains(&file_name)
+}
+
+/// Gets the path to the local config directory of the account of the given SteamID64 under the given installation
+/// directory, see [`Steam::userdata_config_path`].
+fn userdata_config_path(dir: &Path, steamid: u64) -> PathBuf {
+    let account_id = steamid as u32;
+    dir.join("userdata")
+        .join(account_id.to_string())
+        .join("config")
+}
+
+/// Formats the file version of a version resource, given its `VS_FIXEDFILEINFO`'s `dwFileVersionMS` and
+/// `dwFileVersionLS`, as `major.minor.build.revision`.
+fn file_version(ms: u32, ls: u32) -> String {
+    let (major, minor) = (ms >> 16, ms & 0xFFFF);
+    let (build, revision) = (ls >> 16, ls & 0xFFFF);
+    format!("{major}.{minor}.{build}.{revision}")
 }
 
 /// Converts an error [`Option`] into a [`Result`](::std::result::Result).
@@ -154,6 +275,44 @@ fn err_opt<T, E>(error: Option<E>, value: T) -> ::std::result::Result<T, E> {
     }
 }
 
+/// Appends a command-line argument, quoted per the Windows C runtime's parsing rules.
+fn push_arg(command_line: &mut Vec<wchar_t>, arg: &OsStr) {
+    const QUOTE: wchar_t = b'"' as _;
+    const BACKSLASH: wchar_t = b'\\' as _;
+    const SPACE: wchar_t = b' ' as _;
+    const TAB: wchar_t = b'\t' as _;
+
+    command_line.push(SPACE);
+    let needs_quotes = arg.is_empty()
+        || arg
+            .encode_wide()
+            .any(|c| c == SPACE || c == TAB || c == QUOTE);
+    if !needs_quotes {
+        command_line.extend(arg.encode_wide());
+        return;
+    }
+
+    command_line.push(QUOTE);
+    let mut backslashes = 0;
+    for c in arg.encode_wide() {
+        match c {
+            BACKSLASH => backslashes += 1,
+            QUOTE => {
+                command_line.extend(std::iter::repeat_n(BACKSLASH, backslashes * 2 + 1));
+                command_line.push(QUOTE);
+                backslashes = 0;
+            }
+            _ => {
+                command_line.extend(std::iter::repeat_n(BACKSLASH, backslashes));
+                command_line.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    command_line.extend(std::iter::repeat_n(BACKSLASH, backslashes * 2));
+    command_line.push(QUOTE);
+}
+
 /// A [`Steam`] [`Result`](::std::result::Result) type.
 pub type Result<T> = ::std::result::Result<T, Error>;
 
@@ -166,22 +325,98 @@ impl Steam {
             path: [0; MAX_PATH],
         };
         err_opt(unsafe { steam_init(&mut steam) }.into(), steam)
+            .inspect(|steam| log::debug!("found Steam at {}", steam.path().display()))
+    }
+
+    /// Checks whether Steam is installed, per the `SteamPath` registry value, without creating a handle.
+    ///
+    /// Any failure to read the value counts as not installed.
+    #[inline]
+    pub fn is_installed() -> bool {
+        registry::steam_path().is_ok()
+    }
+
+    /// Creates a [`Steam`] handle for the installation in the given directory, rather than the one in the
+    /// registry.
+    ///
+    /// Fails with [`Error::FindSteam`] if the directory doesn't contain `steam.exe`.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let exe = std::path::absolute(dir.join("steam.exe")).map_err(Error::FindSteam)?;
+        let exe = exe.as_os_str().encode_wide().collect::<Vec<_>>();
+        let mut steam = Steam {
+            len: 0,
+            path: [0; MAX_PATH],
+        };
+        err_opt(
+            unsafe { steam_init_path(&mut steam, exe.as_ptr(), exe.len()) }.into(),
+            steam,
+        )
+        .inspect(|steam| log::debug!("found Steam at {}", steam.path().display()))
+    }
+
+    /// Gets the path to the Steam installation directory.
+    ///
+    /// The path is normalized to lowercase with backslash separators.
+    pub fn path(&self) -> PathBuf {
+        let exe = self.client_path();
+        exe.parent().map(Path::to_path_buf).unwrap_or(exe)
+    }
+
+    /// Gets the path to the Steam client's executable, `steam.exe`, in the installation directory.
+    ///
+    /// The path is normalized like [`Self::path`].
+    pub fn client_path(&self) -> PathBuf {
+        PathBuf::from(OsString::from_wide(&self.path[..self.len as usize]))
     }
 
     /// Gracefully and asynchronously shuts down Steam, if running.
     #[inline]
     pub fn start_shutdown(&self) -> Result<()> {
+        log::debug!("requesting Steam to shut down");
         err_opt(unsafe { steam_shutdown(self) }.into(), ())
     }
 
-    /// Gracefully shuts down Steam, if running, and polls until all Steam processes are shut down.
+    /// Gracefully shuts down Steam, if running, and polls at the given interval until all Steam processes are shut
+    /// down, or the timeout elapses.
+    ///
+    /// Returns whether Steam shut down in time.
     #[inline]
-    pub fn shutdown_poll(&self, poll: Duration) -> Result<()> {
+    pub fn shutdown_poll(&self, interval: Duration, timeout: Duration) -> Result<bool> {
+        let start = Instant::now();
         self.start_shutdown()?;
         while self.is_running()? {
-            std::thread::sleep(poll)
+            if start.elapsed() >= timeout {
+                log::debug!("Steam is still running after {:?}", start.elapsed());
+                return Ok(false);
+            }
+            std::thread::sleep(interval)
+        }
+        log::debug!("Steam shut down after {:?}", start.elapsed());
+        Ok(true)
+    }
+
+    /// Waits up to the given timeout for all Steam processes to exit, e.g. after [starting a
+    /// shutdown](Self::start_shutdown).
+    ///
+    /// Waits on the processes' handles rather than polling. Returns whether Steam exited in time.
+    pub fn wait_for_exit(&self, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        // processes may start while waiting, e.g. the one handling the shutdown request, so re-check until none
+        // are left.
+        loop {
+            let processes = self.processes()?;
+            if processes.is_empty() {
+                return Ok(true);
+            }
+            for process in processes {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if !process.wait(remaining).map_err(Error::WaitSteamExit)? {
+                    log::debug!("{} (PID {}) is still running", process.path, process.pid);
+                    return Ok(false);
+                }
+                log::trace!("{} (PID {}) exited", process.path, process.pid);
+            }
         }
-        Ok(())
     }
 
     /// Launches Steam.
@@ -189,27 +424,91 @@ impl Steam {
     /// See also: [`Self::launch_fast`].
     #[inline]
     pub fn launch(&self) -> Result<()> {
+        log::debug!("launching Steam");
         err_opt(unsafe { steam_launch(self) }.into(), ())
     }
 
     /// Launches Steam, skipping Steam's file checks.
     #[inline]
     pub fn launch_fast(&self) -> Result<()> {
+        log::debug!("launching Steam with -noverifyfiles");
         err_opt(unsafe { steam_launch_fast(self) }.into(), ())
     }
 
-    /// Kills all Steam processes.
+    /// Launches Steam with the given arguments.
     ///
-    /// Returns whether any were found and killed.
+    /// Fails with [`Error::LaunchSteam`] if an argument contains a NUL character.
+    pub fn launch_with_args(&self, args: &[&OsStr]) -> Result<()> {
+        let mut command_line = Vec::with_capacity(self.len as usize + 3);
+        command_line.push(b'"' as _);
+        command_line.extend_from_slice(&self.path[..self.len as usize]);
+        command_line.push(b'"' as _);
+        for arg in args {
+            if arg.encode_wide().any(|c| c == 0) {
+                return Err(Error::LaunchSteam(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "launch arguments must not contain NUL characters",
+                )));
+            }
+            push_arg(&mut command_line, arg);
+        }
+        command_line.push(0);
+        log::debug!("launching Steam with {args:?}");
+        err_opt(
+            unsafe { steam_launch_with_args(self, command_line.as_mut_ptr()) }.into(),
+            (),
+        )
+    }
+
+    /// Kills all Steam client processes, excluding helpers such as the Steam Client Service (see also
+    /// [`Self::kill_with_helpers`]).
+    ///
+    /// Returns how many processes were killed. The count covers every process running from Steam's directory, e.g.
+    /// `steamwebhelper.exe` children, not just `steam.exe`.
     #[inline]
-    pub fn kill(&self) -> Result<bool> {
-        let mut killed = 0u8;
-        err_opt(unsafe { steam_kill(self, &mut killed) }.into(), killed != 0)
+    pub fn kill(&self) -> Result<usize> {
+        self.kill_with_helpers(false)
+    }
+
+    /// Kills all Steam client processes like [`Self::kill`], returning whether any were found and killed.
+    #[inline]
+    pub fn kill_any(&self) -> Result<bool> {
+        self.kill().map(|killed| killed > 0)
+    }
+
+    /// Kills all Steam client processes, and helpers such as the Steam Client Service (`steamservice.exe`) if
+    /// `with_helpers` is set.
+    ///
+    /// Returns how many processes were killed, including helper processes. Processes that can't be killed are logged
+    /// and skipped, failing only if none of them could be killed.
+    #[inline]
+    pub fn kill_with_helpers(&self, with_helpers: bool) -> Result<usize> {
+        let mut killed = 0;
+        let mut error = None;
+        for process in self.processes()? {
+            if !should_kill(process.file_name(), with_helpers) {
+                log::trace!("sparing helper {} (PID {})", process.path, process.pid);
+                continue;
+            }
+            log::debug!("killing {} (PID {})", process.path, process.pid);
+            match process.terminate() {
+                Ok(()) => killed += 1,
+                Err(e) => {
+                    log::warn!("failed to kill {} (PID {}): {e}", process.path, process.pid);
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        match error {
+            Some(e) if killed == 0 => Err(Error::KillSteam(e)),
+            _ => Ok(killed),
+        }
     }
 
     /// Sets the Steam user that Steam will attempt to automatically log into.
     #[inline]
     pub fn set_auto_login_user(username: Username) -> Result<()> {
+        log::debug!("writing the auto-login user {username} to the registry");
         let username = username.as_bytes_with_nul();
         err_opt(
             unsafe { steam_set_auto_login_user(username.as_ptr() as *const i8, username.len()) }
@@ -218,7 +517,19 @@ impl Steam {
         )
     }
 
+    /// Removes the auto-login user, so Steam asks which account to log into on its next launch.
+    ///
+    /// Succeeds if there's no auto-login user to begin with.
+    #[inline]
+    pub fn clear_auto_login_user() -> Result<()> {
+        log::debug!("deleting the auto-login user from the registry");
+        err_opt(unsafe { steam_clear_auto_login_user() }.into(), ())
+    }
+
     /// Gets the Steam user that Steam will attempt to automatically log into.
+    ///
+    /// Fails with [`Error::ReadSteamRegistry`] of [`io::ErrorKind::NotFound`] if there's none, i.e. the value is
+    /// missing or empty.
     #[inline]
     pub fn get_auto_login_user() -> Result<Username> {
         let mut data = [MaybeUninit::<u8>::uninit(); Username::MAX_LEN + 1];
@@ -228,17 +539,160 @@ impl Steam {
             (),
         )?;
         let username = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, len - 1) };
+        // Steam treats an empty value as no auto-login user.
+        if username.is_empty() {
+            log::debug!("the auto-login user in the registry is empty");
+            return Err(Error::ReadSteamRegistry(io::Error::from(
+                io::ErrorKind::NotFound,
+            )));
+        }
+        log::debug!(
+            "read the auto-login user {} from the registry",
+            username.escape_ascii()
+        );
         Username::try_from(username).map_err(Error::InvalidUsernameInRegistry)
     }
 
-    /// Checks if the Steam client is running.
+    /// Gets the auto-login user's registry value as is, without validating it as a [`Username`], e.g. for diagnosing
+    /// an invalid value. Unlike [`Self::get_auto_login_user`], isn't limited to [`Username::MAX_LEN`].
+    ///
+    /// The value is read in the ANSI code page, like [`Self::get_auto_login_user`] reads it, and nothing in it is
+    /// replaced. Fails with [`Error::ReadSteamRegistry`] of [`io::ErrorKind::NotFound`] if there's none, i.e. the
+    /// value is missing or empty.
+    pub fn get_auto_login_user_raw() -> Result<Vec<u8>> {
+        let mut data = vec![0u8; Username::MAX_LEN + 1];
+        loop {
+            let mut size = data.len() as DWORD;
+            let result = unsafe {
+                steam_get_auto_login_user_raw(data.as_mut_ptr() as *mut c_char, &mut size)
+            };
+            if result.phase == CPhase::ReadSteamRegistry && result.win_code == ERROR_MORE_DATA {
+                // the value may grow again between the calls, hence the loop.
+                data.resize(size as usize, 0);
+                continue;
+            }
+            err_opt(result.into(), ())?;
+            // the size includes the NUL terminator.
+            data.truncate((size as usize).saturating_sub(1));
+            break;
+        }
+        if data.is_empty() {
+            log::debug!("the auto-login user in the registry is empty");
+            return Err(Error::ReadSteamRegistry(io::Error::from(
+                io::ErrorKind::NotFound,
+            )));
+        }
+        Ok(data)
+    }
+
+    /// Sets whether Steam remembers the password of the account it logs into, per the `RememberPassword` registry value.
+    ///
+    /// Clearing it makes Steam prompt for the password on its next launch. Note Steam may overwrite the value as it
+    /// shuts down, so it's best set while Steam isn't running.
     #[inline]
-    pub fn is_running(&self) -> Result<bool> {
-        let mut is_running = 0;
+    pub fn set_remember_password(remember: bool) -> Result<()> {
+        log::debug!("writing RememberPassword = {remember} to the registry");
+        registry::set_dword("RememberPassword", remember.into())
+    }
+
+    /// Gets whether Steam remembers the password of the account it logs into, per the `RememberPassword` registry
+    /// value.
+    #[inline]
+    pub fn get_remember_password() -> Result<bool> {
+        registry::get_dword("RememberPassword").map(|value| value != 0)
+    }
+
+    /// Checks that Steam's registry subkey can be read and written, without changing it.
+    ///
+    /// Fails with [`Error::ReadSteamRegistry`] or [`Error::WriteSteamRegistry`] respectively.
+    #[inline]
+    pub fn check_registry_access() -> Result<()> {
+        err_opt(unsafe { steam_registry_check_access() }.into(), ())
+    }
+
+    /// Gets the SteamID64 of the auto-login user, per its record in loginusers.vdf.
+    ///
+    /// Steam doesn't keep the auto-login user's SteamID in the registry, so it's looked up by username.
+    pub fn get_auto_login_steamid(&self) -> Result<u64> {
+        let username = Self::get_auto_login_user()?;
+        let mut source = Vec::new();
+        self.vdf_loginusers()?
+            .read_to_end(&mut source)
+            .map_err(Error::VdfRead)?;
+        let document = vdf::scan_parse(&source).map_err(Error::VdfParse)?;
+        let steamid = vdf::LoginUser::from_vdf(&document)
+            .map_err(Error::VdfLoginUsers)?
+            .filter_map(::std::result::Result::ok)
+            .find(|user| user.username.eq_ignore_ascii_case(username.as_bytes()))
+            .and_then(|user| user.steam_id());
+        steamid
+            .map(u64::from)
+            .ok_or(Error::SteamIdNotFound(username))
+    }
+
+    /// Gets the account ID (the lower 32 bits of the SteamID64) of the user currently logged into Steam.
+    ///
+    /// Returns [`None`] if no user is logged in.
+    #[inline]
+    pub fn get_active_user() -> Result<Option<u32>> {
+        let mut account_id = 0;
         err_opt(
-            unsafe { steam_is_running(self, &mut is_running) }.into(),
-            is_running != 0,
+            unsafe { steam_get_active_user(&mut account_id) }.into(),
+            (account_id != 0).then_some(account_id),
         )
+        .inspect(|account_id| log::trace!("read the active user {account_id:?} from the registry"))
+    }
+
+    /// Checks if the Steam client is running.
+    ///
+    /// See also [`Self::is_fully_stopped`].
+    #[inline]
+    pub fn is_running(&self) -> Result<bool> {
+        Ok(!self.processes()?.is_empty())
+    }
+
+    /// The executables of the processes that make up a running Steam, including the web helpers that may linger
+    /// after the client exits, holding the account locked while they do.
+    ///
+    /// Excludes [helpers](HELPERS) such as the Steam Client Service, which stay resident regardless of the client.
+    pub const PROCESSES: &'static [&'static str] = &["steam.exe", "steamwebhelper.exe"];
+
+    /// Checks that none of Steam's [processes](Self::PROCESSES) are alive, wherever they run from.
+    ///
+    /// Unlike [`Self::is_running`], this also catches processes that outlive a graceful shutdown.
+    #[inline]
+    pub fn is_fully_stopped(&self) -> Result<bool> {
+        match process::find_named(Self::PROCESSES).map_err(Error::EnumProcesses)? {
+            Some((name, pid)) => {
+                log::debug!("{name} (PID {pid}) is still alive");
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Gets the process ID of the running Steam client, if any.
+    #[inline]
+    pub fn pid(&self) -> Result<Option<u32>> {
+        let exe = OsString::from_wide(&self.path[..self.len as usize])
+            .to_string_lossy()
+            .to_lowercase();
+        Ok(self
+            .processes()?
+            .into_iter()
+            .find(|process| process.path == exe)
+            .map(|process| process.pid))
+    }
+
+    /// Lists the processes running from the Steam installation directory.
+    fn processes(&self) -> Result<Vec<Process>> {
+        let dir_len = self.len as usize - "steam.exe".len();
+        let dir = OsString::from_wide(&self.path[..dir_len])
+            .to_string_lossy()
+            .to_lowercase();
+        let processes = process::in_dir(&dir).map_err(Error::EnumProcesses)?;
+        log::trace!("found {} processes in {dir}", processes.len());
+        Ok(processes)
     }
 
     /// Gets a [file handle](File) to the `loginusers.vdf` file.
@@ -251,4 +705,176 @@ impl Steam {
         )?;
         Ok(unsafe { File::from_raw_handle(handle) })
     }
+
+    /// Gets the path to the local config directory of the account of the given SteamID64, if it exists.
+    ///
+    /// The directory is `userdata/<account ID>/config` under the installation directory, where the account ID is
+    /// the lower 32 bits of the SteamID64.
+    pub fn userdata_config_path(&self, steamid: u64) -> Option<PathBuf> {
+        let path = userdata_config_path(&self.path(), steamid);
+        path.is_dir().then_some(path)
+    }
+
+    /// Gets a [file handle](File) to the `localconfig.vdf` file of the account of the given SteamID64.
+    pub fn vdf_localconfig(&self, steamid: u64) -> Result<File> {
+        let config = self
+            .userdata_config_path(steamid)
+            .ok_or_else(|| Error::VdfOpen(io::ErrorKind::NotFound.into()))?;
+        File::open(config.join("localconfig.vdf")).map_err(Error::VdfOpen)
+    }
+
+    /// Gets a [file handle](File) to the `steamapps/libraryfolders.vdf` file.
+    pub fn vdf_libraryfolders(&self) -> Result<File> {
+        File::open(self.path().join("steamapps").join("libraryfolders.vdf")).map_err(Error::VdfOpen)
+    }
+
+    /// Gets the paths of the Steam library folders, per `libraryfolders.vdf`.
+    ///
+    /// Supports both the old format, where each numbered entry is the path itself, and the newer one, where each
+    /// numbered entry has subkeys including its `path`. Folders that no longer exist are skipped.
+    pub fn library_folders(&self) -> Result<Vec<PathBuf>> {
+        let mut source = Vec::new();
+        self.vdf_libraryfolders()?
+            .read_to_end(&mut source)
+            .map_err(Error::VdfRead)?;
+        let document = vdf::scan_parse(&source).map_err(Error::VdfParse)?;
+        // the root key is "LibraryFolders" in the old format, and "libraryfolders" in the newer one.
+        let folders = document
+            .children(vdf::ExprId::ROOT)
+            .find(|entry| entry.key.eq_ignore_ascii_case(b"libraryfolders"))
+            .and_then(|entry| match entry.value {
+                vdf::Value::Subkeys(folders) => Some(folders),
+                _ => None,
+            })
+            .ok_or(Error::VdfLibraryFolders)?;
+        Ok(document
+            .children(folders)
+            // other keys, e.g. "ContentStatsID", aren't folders.
+            .filter(|entry| !entry.key.is_empty() && entry.key.iter().all(u8::is_ascii_digit))
+            .filter_map(|entry| match &entry.value {
+                vdf::Value::String(path) => Some(path.as_ref()),
+                vdf::Value::Subkeys(folder) => document.value_str(*folder, b"path"),
+                vdf::Value::Int(_) => None,
+            })
+            .map(|path| PathBuf::from(String::from_utf8_lossy(&vdf::unescape(path)).into_owned()))
+            .filter(|path| path.is_dir())
+            .collect())
+    }
+
+    /// Gets the Steam client's version, per its executable's version resource.
+    #[inline]
+    pub fn client_version(&self) -> Result<String> {
+        let mut version = [0u32; 2];
+        err_opt(
+            unsafe { steam_client_version(self, version.as_mut_ptr()) }.into(),
+            (),
+        )?;
+        let [ms, ls] = version;
+        Ok(file_version(ms, ls))
+    }
+}
+
+impl RegistryWatch {
+    /// Attempts to create a new [`RegistryWatch`].
+    ///
+    /// The watch needs to be [armed](Self::arm) to be notified of changes.
+    #[inline]
+    pub fn new() -> Result<Self> {
+        let mut watch = RegistryWatch {
+            key: std::ptr::null_mut(),
+            event: std::ptr::null_mut(),
+        };
+        err_opt(
+            unsafe { steam_registry_watch_init(&mut watch) }.into(),
+            watch,
+        )
+    }
+
+    /// Arms the watch to be notified of the next change.
+    ///
+    /// Must be called again after each notification, from the thread that [waits](Self::wait) on it.
+    #[inline]
+    pub fn arm(&self) -> Result<()> {
+        err_opt(unsafe { steam_registry_watch_arm(self) }.into(), ())
+    }
+
+    /// Waits up to the given timeout for the armed watch to be notified of a change.
+    ///
+    /// Returns whether a change was notified.
+    #[inline]
+    pub fn wait(&self, timeout: Duration) -> Result<bool> {
+        let mut changed = 0;
+        let timeout_ms = timeout.as_millis().min(DWORD::MAX as u128 - 1) as DWORD;
+        err_opt(
+            unsafe { steam_registry_watch_wait(self, timeout_ms, &mut changed) }.into(),
+            changed != 0,
+        )
+    }
+}
+
+impl Drop for RegistryWatch {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { steam_registry_watch_close(self) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kill_spares_helpers() {
+        assert!(should_kill("steam.exe", false));
+        assert!(should_kill("steamwebhelper.exe", false));
+        assert!(!should_kill("steamservice.exe", false));
+    }
+
+    #[test]
+    fn kill_with_helpers() {
+        assert!(should_kill("steam.exe", true));
+        assert!(should_kill("steamwebhelper.exe", true));
+        assert!(should_kill("steamservice.exe", true));
+    }
+
+    #[test]
+    fn stopped_despite_helpers() {
+        assert!(Steam::PROCESSES
+            .iter()
+            .all(|process| should_kill(process, false)));
+    }
+
+    #[test]
+    fn userdata_config_path_layout() {
+        let dir = Path::new(r"C:\Program Files (x86)\Steam");
+        let config = userdata_config_path(dir, 76561197960287930);
+        assert_eq!(
+            config,
+            Path::new(r"C:\Program Files (x86)\Steam\userdata\22202\config")
+        );
+        assert_eq!(
+            config.join("localconfig.vdf"),
+            Path::new(r"C:\Program Files (x86)\Steam\userdata\22202\config\localconfig.vdf")
+        );
+    }
+
+    #[test]
+    fn file_version_fields() {
+        assert_eq!(file_version(0x0002_000A, 0x005B_0062), "2.10.91.98");
+        assert_eq!(file_version(0, 0), "0.0.0.0");
+        assert_eq!(file_version(u32::MAX, u32::MAX), "65535.65535.65535.65535");
+    }
+
+    #[cfg(feature = "live")]
+    #[test]
+    fn live_path() {
+        let path = Steam::new().unwrap().path();
+        assert!(path.is_dir(), "{} isn't a directory", path.display());
+        assert!(
+            path.file_name()
+                .is_some_and(|name| name.eq_ignore_ascii_case("steam")),
+            "{} isn't named Steam",
+            path.display()
+        );
+    }
 }
@@ -0,0 +1,148 @@
+//! Access to string values in Steam's registry subkey, through the wide (Unicode) registry API.
+//!
+//! Unlike the auto-login user, which is ASCII, values such as paths may contain any text.
+
+use std::{
+    ffi::{OsStr, OsString},
+    io,
+    os::windows::prelude::{OsStrExt, OsStringExt},
+    path::PathBuf,
+};
+
+use winapi::{
+    ctypes::wchar_t,
+    shared::{
+        minwindef::{DWORD, MAX_PATH},
+        winerror::ERROR_MORE_DATA,
+    },
+};
+
+use super::{
+    err_opt, steam_registry_get_dword, steam_registry_get_string, steam_registry_set_dword,
+    steam_registry_set_string, CPhase, Result,
+};
+
+/// Encodes the text as a NUL terminated wide string.
+fn to_wide(text: &OsStr) -> Vec<wchar_t> {
+    text.encode_wide().chain(Some(0)).collect()
+}
+
+/// Gets the string value of the given name in Steam's registry subkey.
+///
+/// Fails with [`Error::ReadSteamRegistry`](super::Error::ReadSteamRegistry) if the value can't be read, e.g. with
+/// [`io::ErrorKind::NotFound`] if it's missing, or if it isn't a string.
+pub fn get_string(name: &str) -> Result<OsString> {
+    let name = to_wide(name.as_ref());
+    let mut data = vec![0 as wchar_t; MAX_PATH];
+    loop {
+        let mut size = (data.len() * size_of::<wchar_t>()) as DWORD;
+        let result =
+            unsafe { steam_registry_get_string(name.as_ptr(), data.as_mut_ptr(), &mut size) };
+        if result.phase == CPhase::ReadSteamRegistry && result.win_code == ERROR_MORE_DATA {
+            // the value may grow again between the calls, hence the loop.
+            data.resize(size as usize / size_of::<wchar_t>(), 0);
+            continue;
+        }
+        err_opt(result.into(), ())?;
+        let len = size as usize / size_of::<wchar_t>();
+        // the size includes the NUL terminator.
+        data.truncate(len.saturating_sub(1));
+        break Ok(OsString::from_wide(&data));
+    }
+}
+
+/// Sets the string value of the given name in Steam's registry subkey.
+///
+/// Fails with [`Error::WriteSteamRegistry`](super::Error::WriteSteamRegistry) if the value can't be written, e.g.
+/// with [`io::ErrorKind::InvalidInput`] if it's too long.
+pub fn set_string(name: &str, value: &OsStr) -> Result<()> {
+    let name = to_wide(name.as_ref());
+    let data = to_wide(value);
+    let Ok(size) = DWORD::try_from(data.len() * size_of::<wchar_t>()) else {
+        return Err(super::Error::WriteSteamRegistry(io::Error::from(
+            io::ErrorKind::InvalidInput,
+        )));
+    };
+    err_opt(
+        unsafe { steam_registry_set_string(name.as_ptr(), data.as_ptr(), size) }.into(),
+        (),
+    )
+}
+
+/// Gets the DWORD value of the given name in Steam's registry subkey.
+///
+/// Fails with [`Error::ReadSteamRegistry`](super::Error::ReadSteamRegistry) if the value can't be read, e.g. with
+/// [`io::ErrorKind::NotFound`] if it's missing, or if it isn't a DWORD.
+pub fn get_dword(name: &str) -> Result<u32> {
+    let name = to_wide(name.as_ref());
+    let mut value = 0;
+    err_opt(
+        unsafe { steam_registry_get_dword(name.as_ptr(), &mut value) }.into(),
+        (),
+    )?;
+    Ok(value)
+}
+
+/// Sets the DWORD value of the given name in Steam's registry subkey.
+///
+/// Fails with [`Error::WriteSteamRegistry`](super::Error::WriteSteamRegistry) if the value can't be written.
+pub fn set_dword(name: &str, value: u32) -> Result<()> {
+    let name = to_wide(name.as_ref());
+    err_opt(
+        unsafe { steam_registry_set_dword(name.as_ptr(), value) }.into(),
+        (),
+    )
+}
+
+/// Gets the path value of the given name, failing with a descriptive [`io::ErrorKind::NotFound`] error if it's
+/// missing.
+fn get_path(name: &str) -> io::Result<PathBuf> {
+    match get_string(name) {
+        Ok(path) => Ok(PathBuf::from(path)),
+        Err(super::Error::ReadSteamRegistry(e)) if e.kind() == io::ErrorKind::NotFound => {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Steam's registry subkey has no {name} value, is Steam installed?"),
+            ))
+        }
+        Err(super::Error::ReadSteamRegistry(e)) => Err(e),
+        Err(e) => Err(io::Error::other(e)),
+    }
+}
+
+/// Gets the path to Steam's installation directory, per the `SteamPath` value.
+///
+/// Unlike [`Steam::new`](super::Steam::new), doesn't normalize the path, so it's as Steam wrote it (typically with
+/// forward slashes).
+#[inline]
+pub fn steam_path() -> io::Result<PathBuf> {
+    get_path("SteamPath")
+}
+
+/// Gets the path to Steam's executable, per the `SteamExe` value.
+///
+/// Unlike [`Steam::new`](super::Steam::new), doesn't normalize the path, so it's as Steam wrote it (typically with
+/// forward slashes).
+#[inline]
+pub fn steam_exe() -> io::Result<PathBuf> {
+    get_path("SteamExe")
+}
+
+#[cfg(all(test, feature = "live"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_paths() {
+        let path = steam_path().unwrap();
+        let exe = steam_exe().unwrap();
+        assert!(path.is_dir(), "{} isn't a directory", path.display());
+        assert!(exe.is_file(), "{} isn't a file", exe.display());
+        let path = path.to_string_lossy().to_lowercase();
+        let exe = exe.to_string_lossy().to_lowercase();
+        assert!(
+            exe.starts_with(&path),
+            "{exe} isn't in Steam's directory {path}"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! SteamID representations.
+
+use std::{fmt::Display, num::ParseIntError, str::FromStr};
+
+/// A SteamID, held in its 64-bit representation (SteamID64).
+///
+/// From the most significant bits: the universe (8 bits), the account type (4 bits), the instance (20 bits) and the
+/// account ID (32 bits).
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct SteamId(pub u64);
+
+impl SteamId {
+    /// The account type of individual (user) accounts.
+    pub const TYPE_INDIVIDUAL: u8 = 1;
+
+    /// Gets the account ID, which is unique per account within its universe and type.
+    #[inline]
+    pub const fn account_id(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// Gets the instance, which is 1 for individual accounts on desktop.
+    #[inline]
+    pub const fn instance(self) -> u32 {
+        (self.0 >> 32) as u32 & 0xF_FFFF
+    }
+
+    /// Gets the account type, e.g. [`Self::TYPE_INDIVIDUAL`].
+    #[inline]
+    pub const fn account_type(self) -> u8 {
+        (self.0 >> 52) as u8 & 0xF
+    }
+
+    /// Gets the universe, which is 1 for the public universe.
+    #[inline]
+    pub const fn universe(self) -> u8 {
+        (self.0 >> 56) as u8
+    }
+
+    /// Formats the ID in the legacy `STEAM_X:Y:Z` representation, where X is the universe, Y is the lowest bit of
+    /// the account ID and Z is the rest of it.
+    ///
+    /// Some older games print the public universe as 0 rather than 1.
+    pub fn steam2(self) -> String {
+        let account_id = self.account_id();
+        format!(
+            "STEAM_{}:{}:{}",
+            self.universe(),
+            account_id & 1,
+            account_id >> 1
+        )
+    }
+
+    /// Formats the ID in the `[T:U:A]` representation, where T is a letter per the account type, U is the universe
+    /// and A is the account ID, e.g. `[U:1:22202]`.
+    ///
+    /// The instance is appended for the account types it's meaningful for.
+    pub fn steam3(self) -> String {
+        // chat instance flags.
+        const CLAN: u32 = 1 << 19;
+        const LOBBY: u32 = 1 << 18;
+        let letter = match self.account_type() {
+            1 => 'U',
+            2 => 'M',
+            3 => 'G',
+            4 => 'A',
+            5 => 'P',
+            6 => 'C',
+            7 => 'g',
+            8 if self.instance() & CLAN != 0 => 'c',
+            8 if self.instance() & LOBBY != 0 => 'L',
+            8 => 'T',
+            10 => 'a',
+            _ => 'I',
+        };
+        let (universe, account_id) = (self.universe(), self.account_id());
+        match letter {
+            'M' | 'A' => format!("[{letter}:{universe}:{account_id}:{}]", self.instance()),
+            _ => format!("[{letter}:{universe}:{account_id}]"),
+        }
+    }
+}
+
+impl From<u64> for SteamId {
+    #[inline]
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<SteamId> for u64 {
+    #[inline]
+    fn from(value: SteamId) -> Self {
+        value.0
+    }
+}
+
+/// Parses a SteamID64.
+impl FromStr for SteamId {
+    type Err = ParseIntError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// Formats the SteamID64.
+impl Display for SteamId {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An individual account in the public universe.
+    const INDIVIDUAL: SteamId = SteamId(76561197960287930);
+
+    #[test]
+    fn fields() {
+        assert_eq!(INDIVIDUAL.account_id(), 22202);
+        assert_eq!(INDIVIDUAL.instance(), 1);
+        assert_eq!(INDIVIDUAL.account_type(), SteamId::TYPE_INDIVIDUAL);
+        assert_eq!(INDIVIDUAL.universe(), 1);
+    }
+
+    #[test]
+    fn steam2() {
+        assert_eq!(INDIVIDUAL.steam2(), "STEAM_1:0:11101");
+        assert_eq!(SteamId(76561197960287931).steam2(), "STEAM_1:1:11101");
+    }
+
+    #[test]
+    fn steam3() {
+        assert_eq!(INDIVIDUAL.steam3(), "[U:1:22202]");
+        // a clan (group).
+        assert_eq!(SteamId(103582791429521412).steam3(), "[g:1:4]");
+        // an anonymous game server, whose instance is appended.
+        assert_eq!(SteamId(0x0140_0002_0000_0007).steam3(), "[A:1:7:2]");
+        // a lobby, per its chat instance flag.
+        assert_eq!(SteamId(0x0184_0000_0000_0009).steam3(), "[L:1:9]");
+    }
+
+    #[test]
+    fn parse_display() {
+        let steam_id = "76561197960287930".parse::<SteamId>().unwrap();
+        assert_eq!(steam_id, INDIVIDUAL);
+        assert_eq!(steam_id.to_string(), "76561197960287930");
+        assert!("STEAM_1:0:11101".parse::<SteamId>().is_err());
+    }
+}
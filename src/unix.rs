@@ -0,0 +1,551 @@
+//! Steam client operations on Linux and macOS, where Steam keeps its registry in a `registry.vdf` file rather than the
+//! Windows registry: `~/.steam/registry.vdf` on Linux, and `~/Library/Application Support/Steam/registry.vdf` on macOS.
+
+use std::{
+    cell::Cell,
+    env,
+    ffi::OsStr,
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    vdf::{self, ExprId as Id, ScanParseError},
+    Username, UsernameError,
+};
+
+/// The primary error type.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Indicates failure to find the home directory, where Steam keeps its data.
+    #[error("failed to find the home directory, HOME isn't set")]
+    NoHome,
+    /// Indicates failure to find Steam's installation directory.
+    #[error("failed to find Steam's installation directory: {0}")]
+    FindSteam(io::Error),
+    /// Indicates failure to read Steam's registry file.
+    #[error("failed to read Steam's registry file: {0}")]
+    ReadSteamRegistry(io::Error),
+    /// Indicates failure to write Steam's registry file.
+    #[error("failed to write Steam's registry file: {0}")]
+    WriteSteamRegistry(io::Error),
+    /// Indicates failure to parse Steam's registry file.
+    #[error("failed to parse Steam's registry file: {0}")]
+    ParseSteamRegistry(ScanParseError),
+    /// Indicates Steam's registry file has no Steam subkeys, e.g. because Steam never ran.
+    #[error(
+        "missing expected \"Registry/HKCU/Software/Valve/Steam\" subkeys in Steam's registry file"
+    )]
+    SteamRegistryKey,
+    /// Indicates an invalid username was found in Steam's registry file.
+    #[error("the auto-login username in the registry is invalid: {0}")]
+    InvalidUsernameInRegistry(UsernameError),
+    /// Indicates failure to launch Steam.
+    #[error("failed to launch Steam: {0}")]
+    LaunchSteam(io::Error),
+    /// Indicates failure to search for Steam's process.
+    #[error("failed to search for a Steam process: {0}")]
+    EnumProcesses(io::Error),
+    /// Indicates failure to terminate Steam's process.
+    #[error("failed to terminate Steam's process: {0}")]
+    KillSteam(io::Error),
+    /// Indicates failure to open a VDF file.
+    #[error("failed to open a VDF file: {0}")]
+    VdfOpen(io::Error),
+    /// Indicates failure to read the Steam client's version.
+    #[error("failed to read the Steam client's version: {0}")]
+    ReadVersion(io::Error),
+    /// Indicates failure to watch Steam's registry file for changes.
+    #[error("failed to watch Steam's registry file for changes: {0}")]
+    WatchSteamRegistry(io::Error),
+    /// Indicates the auto-login user read back after setting it isn't the one that was set, e.g. because another
+    /// process rewrote it.
+    ///
+    /// Holds the user that was read back.
+    #[error("the auto-login user reads back as {0} after setting it, another process may have changed it")]
+    AutoLoginUserMismatch(Username),
+}
+
+/// Exit codes per `sysexits.h`.
+impl<'a> From<&'a Error> for ExitCode {
+    fn from(e: &'a Error) -> Self {
+        ExitCode::from(match e {
+            Error::InvalidUsernameInRegistry(_) => 78,
+            Error::ParseSteamRegistry(_) | Error::SteamRegistryKey => 65,
+            Error::AutoLoginUserMismatch(_) => 71,
+            _ => 69,
+        })
+    }
+}
+
+/// A [`Steam`] [`Result`](::std::result::Result) type.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// A handle to the installed Steam client.
+#[derive(Debug, Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Steam {
+    path: PathBuf,
+}
+
+/// A watch for changes to Steam's registry file, such as to the auto-login user.
+///
+/// There's no change notification for files that works the same on Linux and macOS, so the watch polls the file's
+/// modification time.
+#[derive(Debug)]
+pub struct RegistryWatch {
+    path: PathBuf,
+    /// The file's modification time when the watch was [armed](Self::arm), [`None`] if it didn't exist.
+    armed: Cell<Option<SystemTime>>,
+}
+
+/// Gets the home directory.
+fn home() -> Result<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from).ok_or(Error::NoHome)
+}
+
+/// Gets the directory of the default Steam installation, which `~/.steam/steam` links to.
+#[cfg(target_os = "linux")]
+fn default_dir() -> Result<PathBuf> {
+    Ok(home()?.join(".steam").join("steam"))
+}
+
+/// Gets the directory of the default Steam installation.
+#[cfg(target_os = "macos")]
+fn default_dir() -> Result<PathBuf> {
+    Ok(home()?.join("Library/Application Support/Steam"))
+}
+
+/// Gets the path to Steam's registry file, which lives in `~/.steam` beside the installation link.
+#[cfg(target_os = "linux")]
+fn registry_path() -> Result<PathBuf> {
+    Ok(home()?.join(".steam").join("registry.vdf"))
+}
+
+/// Gets the path to Steam's registry file, which lives in the default installation directory.
+#[cfg(target_os = "macos")]
+fn registry_path() -> Result<PathBuf> {
+    Ok(default_dir()?.join("registry.vdf"))
+}
+
+/// The path of the Steam client's launcher script, relative to the installation directory.
+#[cfg(target_os = "linux")]
+const CLIENT: &str = "steam.sh";
+/// The path of the Steam client's executable, relative to the installation directory.
+#[cfg(target_os = "macos")]
+const CLIENT: &str = "Steam.AppBundle/Steam/Contents/MacOS/steam_osx";
+
+/// The path of the file that holds the installed Steam client's version, relative to the installation directory.
+#[cfg(target_os = "linux")]
+const VERSION_FILE: &str = "package/steam_client_ubuntu12.installed";
+/// The path of the file that holds the installed Steam client's version, relative to the installation directory.
+#[cfg(target_os = "macos")]
+const VERSION_FILE: &str = "package/steam_client_osx.installed";
+
+/// The name of the Steam client's process.
+#[cfg(target_os = "linux")]
+const PROCESS_NAME: &str = "steam";
+/// The name of the Steam client's process.
+#[cfg(target_os = "macos")]
+const PROCESS_NAME: &str = "steam_osx";
+
+/// The path of the Steam subkeys in the registry file.
+const STEAM_KEY: &[&[u8]] = &[b"Registry", b"HKCU", b"Software", b"Valve", b"Steam"];
+
+/// Finds the Steam subkeys in the registry file, matching keys case-insensitively as Steam does.
+fn steam_key(document: &vdf::Document) -> Option<Id> {
+    STEAM_KEY
+        .iter()
+        .try_fold(Id::ROOT, |at, key| child_subkeys(document, at, key))
+}
+
+/// Finds the subkeys of the given key directly under the given parent, matching the key case-insensitively.
+fn child_subkeys(document: &vdf::Document, at: Id, key: &[u8]) -> Option<Id> {
+    document
+        .children(at)
+        .find(|row| row.key.eq_ignore_ascii_case(key))
+        .and_then(|row| match row.value {
+            vdf::Value::Subkeys(sub) => Some(sub),
+            _ => None,
+        })
+}
+
+/// Finds the string value of the given key directly under the given parent, matching the key case-insensitively.
+fn child_str<'d>(document: &'d vdf::Document, at: Id, key: &[u8]) -> Option<&'d [u8]> {
+    document
+        .children(at)
+        .find(|row| row.key.eq_ignore_ascii_case(key))
+        .and_then(|row| match &row.value {
+            vdf::Value::String(value) => Some(value.as_ref()),
+            _ => None,
+        })
+}
+
+/// Gets the account ID of the user logged into Steam from the `ActiveProcess` subkeys under the given Steam subkeys,
+/// see [`Steam::get_active_user`].
+fn active_user(document: &vdf::Document, steam_key: Id) -> Option<u32> {
+    child_subkeys(document, steam_key, b"ActiveProcess")
+        .and_then(|active| child_str(document, active, b"ActiveUser"))
+        .and_then(|value| std::str::from_utf8(value).ok()?.parse::<u32>().ok())
+        .filter(|&account_id| account_id != 0)
+}
+
+/// Spawns the given command with null standard streams, without waiting for it.
+fn spawn_detached(mut command: Command) -> io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// Finds the PIDs of the Steam client's processes with `pgrep`.
+fn pids() -> io::Result<Vec<String>> {
+    let output = Command::new("pgrep")
+        .args(["-x", PROCESS_NAME])
+        .stderr(Stdio::null())
+        .output()?;
+    // exits with 1 when no process matched, and above it on failure.
+    match output.status.code() {
+        Some(0 | 1) => Ok(String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect()),
+        _ => Err(io::Error::other(format!("pgrep {}", output.status))),
+    }
+}
+
+/// Terminates the process of the given PID with `kill`, returning whether it succeeded.
+fn terminate(pid: &str) -> io::Result<bool> {
+    Command::new("kill")
+        .arg(pid)
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+}
+
+impl Steam {
+    /// Attempts to create a new [`Steam`] handle, for the default installation: the one `~/.steam/steam` links to on
+    /// Linux, or `~/Library/Application Support/Steam` on macOS.
+    pub fn new() -> Result<Self> {
+        Self::from_dir(&default_dir()?)
+    }
+
+    /// Creates a [`Steam`] handle for the installation in the given directory.
+    ///
+    /// Fails with [`Error::FindSteam`] if the directory doesn't exist.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let path = fs::canonicalize(dir).map_err(Error::FindSteam)?;
+        if !path.is_dir() {
+            return Err(Error::FindSteam(io::Error::from(io::ErrorKind::NotFound)));
+        }
+        log::debug!("found Steam at {}", path.display());
+        Ok(Self { path })
+    }
+
+    /// Gets the path to Steam's installation directory.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens Steam's loginusers.vdf file.
+    pub fn vdf_loginusers(&self) -> Result<File> {
+        File::open(self.path.join("config").join("loginusers.vdf")).map_err(Error::VdfOpen)
+    }
+
+    /// Gets the path to Steam's registry file.
+    #[inline]
+    pub fn registry_path() -> Result<PathBuf> {
+        registry_path()
+    }
+
+    /// Gets the path to the Steam client in the installation directory: the `steam.sh` launcher script on Linux, or
+    /// the client's executable in its app bundle on macOS.
+    pub fn client_path(&self) -> PathBuf {
+        self.path.join(CLIENT)
+    }
+
+    /// Launches the Steam client of this installation, see [`Self::client_path`].
+    pub fn launch(&self) -> Result<()> {
+        self.launch_with_args(&[])
+    }
+
+    /// Launches the Steam client of this installation with the given arguments, see [`Self::client_path`].
+    pub fn launch_with_args(&self, args: &[&OsStr]) -> Result<()> {
+        let client = self.client_path();
+        log::debug!("launching Steam through {} with {args:?}", client.display());
+        let mut command = Command::new(client);
+        command.args(args);
+        spawn_detached(command).map_err(Error::LaunchSteam)
+    }
+
+    /// Asks Steam to shut down gracefully, by running this installation's [client](Self::client_path) with
+    /// `-shutdown`.
+    ///
+    /// Doesn't wait for Steam to exit.
+    pub fn start_shutdown(&self) -> Result<()> {
+        log::debug!("requesting Steam to shut down");
+        let mut command = Command::new(self.client_path());
+        command.arg("-shutdown");
+        spawn_detached(command).map_err(Error::LaunchSteam)
+    }
+
+    /// Kills the Steam client's processes, found with `pgrep`, with `kill`.
+    ///
+    /// Returns how many were killed, not counting those that exited by themselves in the meantime. Processes that
+    /// can't be killed are logged and skipped, failing only if none of them could be killed.
+    pub fn kill(&self) -> Result<usize> {
+        let found = pids().map_err(Error::EnumProcesses)?;
+        let mut killed = 0;
+        let mut failed = Vec::new();
+        for pid in found {
+            log::debug!("killing {PROCESS_NAME} (PID {pid})");
+            if terminate(&pid).map_err(Error::KillSteam)? {
+                killed += 1;
+            } else {
+                failed.push(pid);
+            }
+        }
+        if failed.is_empty() {
+            return Ok(killed);
+        }
+        // failing to kill a process that has since exited isn't an error.
+        let alive = pids().map_err(Error::EnumProcesses)?;
+        let failed = failed
+            .into_iter()
+            .filter(|pid| alive.contains(pid))
+            .collect::<Vec<_>>();
+        for pid in &failed {
+            log::warn!("failed to kill {PROCESS_NAME} (PID {pid})");
+        }
+        match failed.first() {
+            Some(pid) if killed == 0 => Err(Error::KillSteam(io::Error::other(format!(
+                "failed to kill {PROCESS_NAME} (PID {pid})"
+            )))),
+            _ => Ok(killed),
+        }
+    }
+
+    /// Checks if the Steam client is running, with `pgrep`.
+    pub fn is_running(&self) -> Result<bool> {
+        pids()
+            .map(|pids| !pids.is_empty())
+            .map_err(Error::EnumProcesses)
+    }
+
+    /// Gets the process ID of the running Steam client, if any, with `pgrep`.
+    ///
+    /// If there are several, gets the lowest.
+    pub fn pid(&self) -> Result<Option<u32>> {
+        Ok(pids()
+            .map_err(Error::EnumProcesses)?
+            .iter()
+            .filter_map(|pid| pid.parse().ok())
+            .min())
+    }
+
+    /// Gets the Steam client's version, per the version file Steam's bootstrapper keeps in the installation.
+    pub fn client_version(&self) -> Result<String> {
+        let version =
+            fs::read_to_string(self.path.join(VERSION_FILE)).map_err(Error::ReadVersion)?;
+        match version.trim() {
+            "" => Err(Error::ReadVersion(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the version file is empty",
+            ))),
+            version => Ok(version.to_owned()),
+        }
+    }
+
+    /// Checks that Steam's registry file can be read and written, and has Steam's subkeys, without changing it.
+    ///
+    /// Fails with [`Error::ReadSteamRegistry`], [`Error::SteamRegistryKey`] or [`Error::WriteSteamRegistry`]
+    /// respectively.
+    pub fn check_registry_access() -> Result<()> {
+        Self::read_steam_key(|_, _| ())?;
+        OpenOptions::new()
+            .write(true)
+            .open(Self::registry_path()?)
+            .map(drop)
+            .map_err(Error::WriteSteamRegistry)
+    }
+
+    /// Gets the Steam user that Steam will attempt to automatically log into.
+    ///
+    /// Fails with [`Error::ReadSteamRegistry`] of [`io::ErrorKind::NotFound`] if there's none, i.e. the value is
+    /// missing or empty.
+    ///
+    /// See also [`Self::get_auto_login_user_raw`].
+    pub fn get_auto_login_user() -> Result<Username> {
+        let username = Self::get_auto_login_user_raw()?;
+        Username::try_from(username.as_slice()).map_err(Error::InvalidUsernameInRegistry)
+    }
+
+    /// Gets the auto-login user's value in the registry file as is, without validating it as a [`Username`], e.g.
+    /// for diagnosing an invalid value.
+    ///
+    /// Fails with [`Error::ReadSteamRegistry`] of [`io::ErrorKind::NotFound`] if there's none, i.e. the value is
+    /// missing or empty.
+    pub fn get_auto_login_user_raw() -> Result<Vec<u8>> {
+        let username = Self::read_steam_key(|document, steam_key| {
+            child_str(document, steam_key, b"AutoLoginUser")
+                .filter(|username| !username.is_empty())
+                .map(<[u8]>::to_vec)
+        })?
+        .ok_or_else(|| Error::ReadSteamRegistry(io::Error::from(io::ErrorKind::NotFound)))?;
+        log::debug!(
+            "read the auto-login user {} from the registry",
+            username.escape_ascii()
+        );
+        Ok(username)
+    }
+
+    /// Sets whether Steam remembers the password of the account it logs into, per the `RememberPassword` value in
+    /// the registry file.
+    ///
+    /// Clearing it makes Steam prompt for the password on its next launch. Like [`Self::set_auto_login_user`], it
+    /// only sticks while Steam isn't running.
+    pub fn set_remember_password(remember: bool) -> Result<()> {
+        log::debug!("writing RememberPassword = {remember} to the registry");
+        Self::write_value(b"RememberPassword", if remember { b"1" } else { b"0" })
+    }
+
+    /// Gets whether Steam remembers the password of the account it logs into, per the `RememberPassword` value in
+    /// the registry file.
+    ///
+    /// Steam only remembers it if the value is there and isn't `0`.
+    pub fn get_remember_password() -> Result<bool> {
+        Self::read_steam_key(|document, steam_key| {
+            child_str(document, steam_key, b"RememberPassword").is_some_and(|value| value != b"0")
+        })
+    }
+
+    /// Gets the account ID (the lower 32 bits of the SteamID64) of the user currently logged into Steam, per the
+    /// `ActiveProcess` subkeys of the registry file.
+    ///
+    /// Returns [`None`] if no user is logged in.
+    pub fn get_active_user() -> Result<Option<u32>> {
+        let account_id = Self::read_steam_key(active_user)?;
+        log::trace!("read the active user {account_id:?} from the registry");
+        Ok(account_id)
+    }
+
+    /// Reads the registry file, and reads from its Steam subkeys with the given function.
+    fn read_steam_key<T>(read: impl FnOnce(&vdf::Document, Id) -> T) -> Result<T> {
+        let source = fs::read(Self::registry_path()?).map_err(Error::ReadSteamRegistry)?;
+        let document = vdf::scan_parse(&source).map_err(Error::ParseSteamRegistry)?;
+        let steam_key = steam_key(&document).ok_or(Error::SteamRegistryKey)?;
+        Ok(read(&document, steam_key))
+    }
+
+    /// Sets the Steam user that Steam will attempt to automatically log into.
+    ///
+    /// Rewrites Steam's registry file through a temporary file beside it, so it's never left half-written. Note Steam
+    /// rewrites the file as it shuts down, so the change only sticks while Steam isn't running.
+    pub fn set_auto_login_user(username: Username) -> Result<()> {
+        log::debug!("writing the auto-login user {username} to the registry");
+        Self::write_value(b"AutoLoginUser", username.as_bytes())
+    }
+
+    /// Empties the auto-login user, so Steam asks which account to log into on its next launch.
+    ///
+    /// Succeeds if there's no auto-login user to begin with.
+    pub fn clear_auto_login_user() -> Result<()> {
+        log::debug!("emptying the auto-login user in the registry");
+        Self::write_value(b"AutoLoginUser", b"")
+    }
+
+    /// Writes the value of the given name under the Steam subkeys of the registry file, see
+    /// [`Self::set_auto_login_user`].
+    fn write_value(name: &[u8], value: &[u8]) -> Result<()> {
+        let path = Self::registry_path()?;
+        let source = fs::read(&path).map_err(Error::ReadSteamRegistry)?;
+        let mut document = vdf::scan_parse(&source).map_err(Error::ParseSteamRegistry)?;
+        let steam_key = steam_key(&document).ok_or(Error::SteamRegistryKey)?;
+        // keep the existing key's case.
+        let key = document
+            .children(steam_key)
+            .find(|row| row.key.eq_ignore_ascii_case(name))
+            .map_or_else(|| name.to_vec(), |row| row.key.to_vec());
+        document.set_value(steam_key, &key, value);
+
+        let temp = path.with_extension("vdf.tmp");
+        fs::write(&temp, document.to_vdf()).map_err(Error::WriteSteamRegistry)?;
+        fs::rename(&temp, &path).map_err(Error::WriteSteamRegistry)
+    }
+}
+
+/// How often a [`RegistryWatch`] polls the registry file.
+const WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+impl RegistryWatch {
+    /// Attempts to create a new [`RegistryWatch`].
+    ///
+    /// The watch needs to be [armed](Self::arm) to be notified of changes.
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            path: registry_path()?,
+            armed: Cell::new(None),
+        })
+    }
+
+    /// Arms the watch to be notified of the next change, i.e. of changes after this call.
+    pub fn arm(&self) -> Result<()> {
+        self.armed.set(self.modified()?);
+        Ok(())
+    }
+
+    /// Waits up to the given timeout for the registry file to change since the watch was armed.
+    ///
+    /// Returns whether it changed.
+    pub fn wait(&self, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.modified()? != self.armed.get() {
+                return Ok(true);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(false);
+            }
+            std::thread::sleep(remaining.min(WATCH_INTERVAL));
+        }
+    }
+
+    /// Gets the registry file's modification time, or [`None`] if it doesn't exist.
+    fn modified(&self) -> Result<Option<SystemTime>> {
+        match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => Ok(Some(modified)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::WatchSteamRegistry(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_in_installation() {
+        let dir = env::temp_dir();
+        let steam = Steam::from_dir(&dir).unwrap();
+        let client = steam.client_path();
+        assert!(client.starts_with(fs::canonicalize(&dir).unwrap()));
+        assert!(client.ends_with(CLIENT));
+    }
+
+    #[test]
+    fn active_user_in_registry() {
+        let mut document = vdf::scan_parse(include_bytes!("vdf/fixtures/registry.vdf")).unwrap();
+        let steam = steam_key(&document).unwrap();
+        assert_eq!(active_user(&document, steam), None);
+        let active = child_subkeys(&document, steam, b"activeprocess").unwrap();
+        document.set_value(active, b"ActiveUser", b"22202");
+        assert_eq!(active_user(&document, steam), Some(22202));
+    }
+}
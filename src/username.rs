@@ -2,6 +2,7 @@
 
 use std::{
     fmt::{self, Debug, Display, Formatter},
+    hash::{Hash, Hasher},
     mem::MaybeUninit,
     str::FromStr,
 };
@@ -20,10 +21,16 @@ mod core {
     /// - be at least [`Username::MIN_LEN`] (3) characters.
     /// - be at most [`Username::MAX_LEN`] (32) characters.
     /// - only consist of characters matching the class `[a-zA-Z0-9_]`.
+    ///
+    /// # Case
+    /// Steam usernames are case-insensitive, so the username is normalized to lowercase (see
+    /// [`Username::as_bytes`]), but the original casing is kept for display (see [`Username::as_display`]).
     #[derive(Clone, Copy)]
     pub struct Username {
         /// The username in lowercase with a NUL-terminator.
         data: [MaybeUninit<u8>; Username::MAX_LEN + /* null terminator */ 1],
+        /// The username in its original casing, without a NUL-terminator.
+        original: [MaybeUninit<u8>; Username::MAX_LEN],
         /// The length of the username, excluding NUL-terminator.
         len: usize,
     }
@@ -43,33 +50,84 @@ mod core {
             // per field invariants
             unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.len) }
         }
-    }
 
-    impl<'a> TryFrom<&'a [u8]> for Username {
-        type Error = UsernameError;
+        /// Gets the username in its original casing, for display purposes.
+        #[inline(always)]
+        pub fn as_display(&self) -> &str {
+            // SAFETY:
+            // per field invariants, a subset of ASCII
+            unsafe {
+                std::str::from_utf8_unchecked(std::slice::from_raw_parts(
+                    self.original.as_ptr() as *const u8,
+                    self.len - /* NUL terminator */ 1,
+                ))
+            }
+        }
 
+        /// Creates a [`Username`] from an ASCII string in a const context (see also [`TryFrom`]).
         #[inline]
-        fn try_from(username: &'a [u8]) -> Result<Self, Self::Error> {
+        pub const fn from_ascii(username: &str) -> Result<Self, UsernameError> {
+            Self::from_bytes(username.as_bytes())
+        }
+
+        /// Validates and creates a [`Username`] from ASCII bytes.
+        const fn from_bytes(username: &[u8]) -> Result<Self, UsernameError> {
             if username.len() > Username::MAX_LEN {
-                Err(UsernameError::TooLong)?;
+                return Err(UsernameError::TooLong);
             }
             if username.len() < Username::MIN_LEN {
-                Err(UsernameError::TooShort)?;
+                return Err(UsernameError::TooShort);
             }
-            let mut data = [MaybeUninit::uninit(); Username::MAX_LEN + 1];
-            for (&src, dst) in username.iter().zip(data.iter_mut()) {
-                if !matches!(src, b'a'..=b'z' | b'0'..=b'9' | b'A'..=b'Z' | b'_') {
-                    Err(UsernameError::IllegalCharacters)?
+            let mut i = 0;
+            while i < username.len() {
+                if !matches!(username[i], b'a'..=b'z' | b'0'..=b'9' | b'A'..=b'Z' | b'_') {
+                    return Err(UsernameError::IllegalCharacters);
                 }
-                *dst = MaybeUninit::new(src.to_ascii_lowercase());
+                i += 1;
+            }
+            // SAFETY: validated above
+            Ok(unsafe { Self::from_bytes_unchecked(username) })
+        }
+
+        /// Creates a [`Username`] from an ASCII string without validating it.
+        ///
+        /// # Safety
+        /// The username must satisfy the [validation rules](Username#validation).
+        #[inline]
+        pub const unsafe fn from_ascii_unchecked(username: &str) -> Self {
+            Self::from_bytes_unchecked(username.as_bytes())
+        }
+
+        /// Creates a [`Username`] from ASCII bytes without validating them.
+        ///
+        /// # Safety
+        /// The username must satisfy the [validation rules](Username#validation).
+        const unsafe fn from_bytes_unchecked(username: &[u8]) -> Self {
+            let mut data = [MaybeUninit::uninit(); Username::MAX_LEN + 1];
+            let mut original = [MaybeUninit::uninit(); Username::MAX_LEN];
+            let mut i = 0;
+            while i < username.len() {
+                data[i] = MaybeUninit::new(username[i].to_ascii_lowercase());
+                original[i] = MaybeUninit::new(username[i]);
+                i += 1;
             }
 
             data[username.len()] = MaybeUninit::new(b'\0');
 
-            Ok(Self {
+            Self {
                 data,
+                original,
                 len: username.len() + /* NUL terminator */ 1,
-            })
+            }
+        }
+    }
+
+    impl<'a> TryFrom<&'a [u8]> for Username {
+        type Error = UsernameError;
+
+        #[inline]
+        fn try_from(username: &'a [u8]) -> Result<Self, Self::Error> {
+            Self::from_bytes(username)
         }
     }
 }
@@ -84,6 +142,7 @@ impl Debug for Username {
                 "data",
                 &format_args!("\"{}\"", self.as_bytes_with_nul().escape_ascii()),
             )
+            .field("original", &self.as_display())
             .field("len", &self.len())
             .finish()
     }
@@ -101,6 +160,41 @@ impl Username {
         let with_nul = self.as_bytes_with_nul();
         &with_nul[..with_nul.len() - 1]
     }
+
+    /// Iterates the normalized, lowercase, username's bytes (see [`Username::as_bytes`]).
+    #[inline]
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.as_bytes().iter().copied()
+    }
+
+    /// Iterates the normalized, lowercase, username's characters (see [`Username::as_bytes`]).
+    #[inline]
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.bytes().map(char::from)
+    }
+
+    /// Creates a [`Username`] like [`TryFrom`], but ignores surrounding ASCII whitespace.
+    #[inline]
+    pub fn try_from_trimmed(username: &str) -> Result<Self, UsernameError> {
+        Username::try_from(username.trim_matches(|c: char| c.is_ascii_whitespace()))
+    }
+}
+
+/// Compares the normalized usernames (see [`Username::as_bytes`]).
+impl PartialEq for Username {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for Username {}
+
+impl Hash for Username {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
 }
 
 impl PartialEq<[u8]> for Username {
@@ -128,6 +222,7 @@ impl FromStr for Username {
     }
 }
 
+/// Gets the normalized, lowercase, username (see [`Username::as_bytes`]).
 impl AsRef<str> for Username {
     #[inline(always)]
     fn as_ref(&self) -> &str {
@@ -136,10 +231,11 @@ impl AsRef<str> for Username {
     }
 }
 
+/// Displays the username in its original casing (see [`Username::as_display`]).
 impl Display for Username {
     #[inline(always)]
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        Display::fmt(AsRef::<str>::as_ref(self), f)
+        Display::fmt(self.as_display(), f)
     }
 }
 
@@ -164,3 +260,116 @@ pub enum UsernameError {
     #[error("the username contains illegal characters, only ASCII alphanumeric characters and underscore (_) are allowed")]
     IllegalCharacters,
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Username {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_display())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Username {
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Username;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a Steam username")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Username::try_from(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trimmed() {
+        let username = Username::try_from("some_user").unwrap();
+        assert_eq!(Username::try_from_trimmed("some_user").unwrap(), username);
+        assert_eq!(
+            Username::try_from_trimmed(" \t some_user\r\n").unwrap(),
+            username
+        );
+        assert!(Username::try_from(" some_user").is_err());
+        assert!(Username::try_from_trimmed("some user").is_err());
+        assert!(Username::try_from_trimmed("   ").is_err());
+    }
+
+    #[test]
+    fn min_len() {
+        assert!(Username::try_from("abc").is_ok());
+        assert!(matches!(
+            Username::try_from("ab"),
+            Err(UsernameError::TooShort)
+        ));
+    }
+
+    #[test]
+    fn max_len() {
+        let max = "a".repeat(Username::MAX_LEN);
+        assert_eq!(
+            Username::try_from(max.as_str()).unwrap().as_bytes(),
+            max.as_bytes()
+        );
+        assert!(matches!(
+            Username::try_from("a".repeat(Username::MAX_LEN + 1).as_str()),
+            Err(UsernameError::TooLong)
+        ));
+    }
+
+    #[test]
+    fn as_display_round_trip() {
+        let username = Username::try_from("Some_User").unwrap();
+        assert_eq!(username.as_display(), "Some_User");
+        assert_eq!(username.as_bytes(), b"some_user");
+    }
+
+    #[test]
+    fn eq_hash() {
+        let set: std::collections::HashSet<Username> = ["some_user", "Some_User", "SOME_USER"]
+            .into_iter()
+            .map(|username| Username::try_from(username).unwrap())
+            .collect();
+        assert_eq!(set.len(), 1);
+        assert_ne!(
+            Username::try_from("abc").unwrap(),
+            Username::try_from("abcd").unwrap()
+        );
+    }
+
+    #[test]
+    fn iterators() {
+        let username = Username::try_from("Some_User").unwrap();
+        assert!(username.bytes().eq(*b"some_user"));
+        assert_eq!(username.chars().collect::<String>(), "some_user");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let username = Username::try_from("some_user").unwrap();
+        let json = serde_json::to_string(&username).unwrap();
+        assert_eq!(json, r#""some_user""#);
+        assert_eq!(serde_json::from_str::<Username>(&json).unwrap(), username);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_invalid() {
+        assert!(serde_json::from_str::<Username>(r#""not a username""#).is_err());
+        assert!(serde_json::from_str::<Username>(r#""ab""#).is_err());
+    }
+}
@@ -1,5 +1,24 @@
+//! General-purpose utilities.
+
 use std::marker::PhantomData;
 
+/// An iterator adapter over [`Result`]s that yields the [`Ok`] values until the first [`Err`], which it keeps
+/// for recovery after iterating.
+///
+/// Useful for feeding a fallible iterator to a consumer that expects plain values, such as feeding the
+/// [`Scanner`](crate::vdf::Scanner)'s tokens to the [parser](crate::vdf::parse), as
+/// [`scan_parse`](crate::vdf::scan_parse) does.
+///
+/// # Example
+///
+/// ```
+/// use diverter::{util::OkIter, vdf::{ScanError, Scanner}};
+///
+/// let mut tokens = OkIter::new(Scanner::new(br#""key" "value" ! "rest""#));
+/// assert_eq!(tokens.by_ref().count(), 2);
+/// assert_eq!(tokens.into_error(), Some(ScanError::UnexpectedToken(b'!')));
+/// ```
+#[derive(Debug, Clone)]
 pub struct OkIter<T, E, I> {
     inner: I,
     error: Option<E>,
@@ -7,6 +26,7 @@ pub struct OkIter<T, E, I> {
 }
 
 impl<T, E, I> OkIter<T, E, I> {
+    /// Creates a new [`OkIter`] over the given iterator.
     pub const fn new(inner: I) -> Self {
         Self {
             inner,
@@ -15,9 +35,15 @@ impl<T, E, I> OkIter<T, E, I> {
         }
     }
 
+    /// Gets the error that stopped the iteration, if any.
     pub const fn to_error(&self) -> Option<&E> {
         self.error.as_ref()
     }
+
+    /// Gets the error that stopped the iteration, if any, consuming the iterator.
+    pub fn into_error(self) -> Option<E> {
+        self.error
+    }
 }
 
 impl<T, E, I> Iterator for OkIter<T, E, I>
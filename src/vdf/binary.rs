@@ -0,0 +1,191 @@
+//! Binary [VDF](https://developer.valvesoftware.com/wiki/KeyValues) processing, as used by e.g. `shortcuts.vdf`.
+//!
+//! Each key-value is a type byte, followed by the key as a NUL-terminated string, followed by the value per its type.
+//! Subkeys are terminated by an end byte (`0x08`).
+
+use std::borrow::Cow;
+
+use super::{Document, ExprId as Id, KeyValue, Value, DEFAULT_MAX_DEPTH};
+
+/// The type byte of subkeys.
+const SUBKEYS: u8 = 0x00;
+/// The type byte of a NUL-terminated string value.
+const STRING: u8 = 0x01;
+/// The type byte of a little-endian 32-bit integer value.
+const INT: u8 = 0x02;
+/// The byte that terminates subkeys.
+const END: u8 = 0x08;
+
+/// Binary VDF parse error.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, thiserror::Error)]
+pub enum Error {
+    /// The data ended in the middle of a key-value, or before the subkeys were terminated.
+    #[error("unexpected end of data at offset {0}")]
+    UnexpectedEof(usize),
+    /// A key-value has a type this parser doesn't support.
+    #[error("unsupported value type {0:#04x} at offset {1}")]
+    UnsupportedType(u8, usize),
+    /// The subkeys are nested deeper than [`DEFAULT_MAX_DEPTH`] levels.
+    #[error("subkeys are nested too deeply at offset {0}")]
+    MaxDepthExceeded(usize),
+}
+
+/// A cursor over the binary data.
+struct Reader<'a> {
+    source: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Reads a single byte.
+    fn byte(&mut self) -> Result<u8, Error> {
+        let byte = *self
+            .source
+            .get(self.offset)
+            .ok_or(Error::UnexpectedEof(self.offset))?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    /// Reads a NUL-terminated string, excluding the NUL.
+    fn string(&mut self) -> Result<&'a [u8], Error> {
+        let rest = &self.source[self.offset..];
+        let len = rest
+            .iter()
+            .position(|&c| c == 0)
+            .ok_or(Error::UnexpectedEof(self.source.len()))?;
+        self.offset += len + 1;
+        Ok(&rest[..len])
+    }
+
+    /// Reads a little-endian 32-bit integer.
+    fn int(&mut self) -> Result<i32, Error> {
+        let bytes = self
+            .source
+            .get(self.offset..self.offset + 4)
+            .ok_or(Error::UnexpectedEof(self.source.len()))?;
+        self.offset += 4;
+        Ok(i32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+/// Parses the key-values under the given parent, up to the end byte, or the end of the data if at the root.
+///
+/// Subkeys may nest up to `depth` levels further.
+fn parse_subkeys<'a>(
+    reader: &mut Reader<'a>,
+    document: &mut Document<'a>,
+    parent: Id,
+    depth: usize,
+) -> Result<(), Error> {
+    loop {
+        if parent == Id::ROOT && reader.offset == reader.source.len() {
+            return Ok(());
+        }
+        let type_offset = reader.offset;
+        let r#type = reader.byte()?;
+        if r#type == END {
+            return Ok(());
+        }
+        let key = reader.string()?;
+        let value = match r#type {
+            // the index the key-value of the subkeys is pushed at, as in the text parser.
+            SUBKEYS => Value::Subkeys(Id(document.len())),
+            STRING => Value::String(Cow::Borrowed(reader.string()?)),
+            INT => Value::Int(reader.int()?),
+            r#type => return Err(Error::UnsupportedType(r#type, type_offset)),
+        };
+        let subkeys = match value {
+            Value::Subkeys(sub) => Some(sub),
+            _ => None,
+        };
+        document.push(KeyValue {
+            parent,
+            key: Cow::Borrowed(key),
+            value,
+        });
+        if let Some(sub) = subkeys {
+            let depth = depth
+                .checked_sub(1)
+                .ok_or(Error::MaxDepthExceeded(type_offset))?;
+            parse_subkeys(reader, document, sub, depth)?;
+        }
+    }
+}
+
+/// Parses a binary VDF [`Document`].
+///
+/// String values are kept verbatim, since binary strings aren't escaped.
+pub fn parse_binary(source: &[u8]) -> Result<Document<'_>, Error> {
+    let mut document = Document::default();
+    let mut reader = Reader { source, offset: 0 };
+    parse_subkeys(&mut reader, &mut document, Id::ROOT, DEFAULT_MAX_DEPTH)?;
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A shortcuts.vdf with a single shortcut.
+    const SHORTCUTS: &[u8] = b"\x00shortcuts\x00\
+        \x000\x00\
+            \x02appid\x00\x2a\x00\x00\x80\
+            \x01AppName\x00Some Game\x00\
+            \x01Exe\x00\"C:\\Games\\game.exe\"\x00\
+            \x02IsHidden\x00\x00\x00\x00\x00\
+            \x00tags\x00\
+                \x010\x00favorite\x00\
+            \x08\
+        \x08\
+    \x08\
+    \x08";
+
+    #[test]
+    fn shortcuts() {
+        let document = parse_binary(SHORTCUTS).unwrap();
+        let shortcuts = document.subkeys(Id::ROOT, b"shortcuts").unwrap();
+        let shortcut = document.subkeys(shortcuts, b"0").unwrap();
+        assert_eq!(
+            document.get_path(&[b"shortcuts", b"0", b"appid"]),
+            Some(&Value::Int(i32::from_le_bytes([0x2a, 0x00, 0x00, 0x80])))
+        );
+        assert_eq!(
+            document.value_str(shortcut, b"AppName"),
+            Some(&b"Some Game"[..])
+        );
+        assert_eq!(
+            document.value_str(shortcut, b"Exe"),
+            Some(&br#""C:\Games\game.exe""#[..])
+        );
+        assert_eq!(document.value_bool(shortcut, b"IsHidden"), Some(false));
+        let tags = document.subkeys(shortcut, b"tags").unwrap();
+        assert_eq!(document.value_str(tags, b"0"), Some(&b"favorite"[..]));
+        assert_eq!(document.len(), 8);
+        assert_eq!([shortcuts, shortcut, tags], [Id(0), Id(1), Id(6)]);
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            parse_binary(&SHORTCUTS[..SHORTCUTS.len() - 2]),
+            Err(Error::UnexpectedEof(SHORTCUTS.len() - 2))
+        );
+    }
+
+    #[test]
+    fn deep_nesting() {
+        assert!(matches!(
+            parse_binary(&b"\x00k\x00".repeat(10_000)),
+            Err(Error::MaxDepthExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn unsupported_type() {
+        assert_eq!(
+            parse_binary(b"\x07key\x00"),
+            Err(Error::UnsupportedType(0x07, 0))
+        );
+    }
+}
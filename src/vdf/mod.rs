@@ -3,31 +3,52 @@
 //! This is used to extract data from Steam's installation, such as [`LoginUser`]s.
 
 mod scanner;
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug, io::BufRead};
 
 pub use scanner::{Error as ScanError, Scanner, Token, TokenType};
 
+mod read_scanner;
+pub use read_scanner::{OwnedToken, ReadScanError, ReadScanner};
+
 mod parser;
-pub use parser::{parse, Error as ParseError, Id as ExprId, Value};
+pub use parser::{
+    parse, parse_with_depth, Document, DocumentIndex, Error as ParseError, Id as ExprId, KeyValue,
+    ParseToken, Value, DEFAULT_MAX_DEPTH,
+};
 
-use crate::util::OkIter;
+pub mod binary;
+pub use binary::{parse_binary, Error as BinaryError};
 
-use self::parser::Document;
+use crate::{util::OkIter, SteamId};
 
 /// A login user record.
 #[derive(Clone, Copy)]
 pub struct LoginUser<'a> {
+    /// The user's SteamID64, as the key of the user's record.
+    pub steamid: &'a [u8],
     /// The user's username.
     pub username: &'a [u8],
     /// The user's nickname.
     pub nickname: &'a [u8],
     /// Whether the user can be auto logged in.
     pub allow_auto_login: bool,
+    /// Whether Steam remembers the user's password.
+    pub remember_password: bool,
+    /// Whether the user is the one that most recently logged in.
+    pub most_recent: bool,
+    /// The time the user last logged in, in seconds since the Unix epoch.
+    ///
+    /// [`None`] if missing or malformed.
+    pub timestamp: Option<u64>,
 }
 
 impl<'a> Debug for LoginUser<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LoginUser")
+            .field(
+                "steamid",
+                &format_args!("\"{}\"", self.steamid.escape_ascii()),
+            )
             .field(
                 "username",
                 &format_args!("\"{}\"", self.username.escape_ascii()),
@@ -37,6 +58,9 @@ impl<'a> Debug for LoginUser<'a> {
                 &format_args!("\"{}\"", self.nickname.escape_ascii()),
             )
             .field("allow_auto_login", &self.allow_auto_login)
+            .field("remember_password", &self.remember_password)
+            .field("most_recent", &self.most_recent)
+            .field("timestamp", &self.timestamp)
             .finish()
     }
 }
@@ -66,28 +90,103 @@ impl<'a> LoginUser<'a> {
         impl Iterator<Item = Result<LoginUser<'a>, LoginUserVdfError>> + 'a,
         LoginUserVdfError,
     > {
-        let users_sub = document
+        let index = document.index();
+        let users_sub = index
             .subkeys(ExprId::ROOT, b"users")
             .ok_or(LoginUserVdfError::ExpectedUsersSubkeys)?;
-        let user_ids = document.0.iter().filter(move |row| row.parent == users_sub);
-        Ok(user_ids.map(|user_sub| {
+        let users = index.children(users_sub).collect::<Vec<_>>();
+        Ok(users.into_iter().map(move |user_sub| {
             if let Value::Subkeys(user_keyvals) = user_sub.value {
                 Ok(Self {
-                    username: document
+                    steamid: &user_sub.key,
+                    username: index
                         .value_str(user_keyvals, b"AccountName")
                         .ok_or(LoginUserVdfError::ExpectedAccountNameKey)?,
-                    nickname: document
+                    nickname: index
                         .value_str(user_keyvals, b"PersonaName")
                         .ok_or(LoginUserVdfError::ExpectedPersonaNameKey)?,
-                    allow_auto_login: document
-                        .value_str(user_keyvals, b"AllowAutoLogin")
-                        .map_or(false, |value| value != b"0"),
+                    allow_auto_login: index
+                        .value_bool(user_keyvals, b"AllowAutoLogin")
+                        .unwrap_or(false),
+                    remember_password: index
+                        .value_bool(user_keyvals, b"RememberPassword")
+                        .unwrap_or(false),
+                    most_recent: index
+                        .value_bool(user_keyvals, b"MostRecent")
+                        .unwrap_or(false),
+                    timestamp: index
+                        .value_i64(user_keyvals, b"Timestamp")
+                        .and_then(|value| u64::try_from(value).ok()),
                 })
             } else {
                 Err(LoginUserVdfError::ExpectedUserEntryToBeSubkeys)
             }
         }))
     }
+
+    /// Parses the user's SteamID, the key of the user's record.
+    ///
+    /// [`None`] if the key isn't a SteamID64.
+    pub fn steam_id(&self) -> Option<SteamId> {
+        std::str::from_utf8(self.steamid).ok()?.parse().ok()
+    }
+
+    /// Reads [`LoginUser`]s from a VDF [`Document`] into [owned](OwnedLoginUser) users.
+    ///
+    /// Fails on the first malformed user, unlike [`Self::from_vdf`], which yields an error per user.
+    pub fn collect_owned(document: &Document) -> Result<Vec<OwnedLoginUser>, LoginUserVdfError> {
+        LoginUser::from_vdf(document)?
+            .map(|user| user.map(OwnedLoginUser::from))
+            .collect()
+    }
+}
+
+/// A [`LoginUser`] that owns its data, see [`LoginUser::collect_owned`].
+///
+/// Text that isn't valid UTF-8 is decoded lossily.
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedLoginUser {
+    /// The user's SteamID64, as the key of the user's record.
+    pub steamid: String,
+    /// The user's username.
+    pub username: String,
+    /// The user's nickname.
+    pub nickname: String,
+    /// Whether the user can be auto logged in.
+    pub allow_auto_login: bool,
+    /// Whether Steam remembers the user's password.
+    pub remember_password: bool,
+    /// Whether the user is the one that most recently logged in.
+    pub most_recent: bool,
+    /// The time the user last logged in, in seconds since the Unix epoch.
+    ///
+    /// [`None`] if missing or malformed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timestamp: Option<u64>,
+}
+
+impl<'a> From<LoginUser<'a>> for OwnedLoginUser {
+    fn from(user: LoginUser<'a>) -> Self {
+        Self {
+            steamid: String::from_utf8_lossy(user.steamid).into_owned(),
+            username: String::from_utf8_lossy(user.username).into_owned(),
+            nickname: String::from_utf8_lossy(user.nickname).into_owned(),
+            allow_auto_login: user.allow_auto_login,
+            remember_password: user.remember_password,
+            most_recent: user.most_recent,
+            timestamp: user.timestamp,
+        }
+    }
+}
+
+impl OwnedLoginUser {
+    /// Sorts users by their last login time, most recent first, and users with an unknown last login time last.
+    ///
+    /// The sort is stable, so users that logged in at the same time keep their order.
+    pub fn sort_by_recency(users: &mut [Self]) {
+        users.sort_by_key(|user| (user.timestamp.is_none(), std::cmp::Reverse(user.timestamp)));
+    }
 }
 
 /// [Scan](ScanError) or [parse](ParseError) error.
@@ -102,7 +201,7 @@ pub enum ScanParseError {
 }
 
 /// Scans and parses the source text.
-pub fn scan_parse(source: &[u8]) -> Result<Document, ScanParseError> {
+pub fn scan_parse(source: &[u8]) -> Result<Document<'_>, ScanParseError> {
     let mut tokens = OkIter::new(Scanner::new(source));
     let result = parse(&mut tokens);
     match tokens.to_error() {
@@ -110,3 +209,178 @@ pub fn scan_parse(source: &[u8]) -> Result<Document, ScanParseError> {
         None => result.map_err(ScanParseError::ParseError),
     }
 }
+
+/// [Read-scan](ReadScanError) or [parse](ParseError) error.
+#[derive(Debug, thiserror::Error)]
+pub enum ReadParseError {
+    /// Read-scan error.
+    #[error("{0}")]
+    ReadScanError(#[from] ReadScanError),
+    /// Parse error.
+    #[error("parsing error: {0}")]
+    ParseError(#[from] ParseError),
+}
+
+/// Scans and parses the source from a reader.
+///
+/// Unlike [`scan_parse`], this doesn't need the whole source in memory, but the [`Document`] owns a copy of each key
+/// and value rather than borrowing them from the source, see [`ReadScanner`].
+pub fn parse_reader(reader: impl BufRead) -> Result<Document<'static>, ReadParseError> {
+    let mut tokens = OkIter::new(ReadScanner::new(reader));
+    let result = parse(&mut tokens);
+    match tokens.into_error() {
+        Some(e) => Err(e.into()),
+        None => result.map_err(ReadParseError::ParseError),
+    }
+}
+
+/// Escapes text for use as the contents of a VDF string, i.e. escapes quotes and backslashes.
+pub fn escape(text: &[u8]) -> Cow<'_, [u8]> {
+    if !text.iter().any(|&c| c == b'"' || c == b'\\') {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = Vec::with_capacity(text.len() + 2);
+    for &c in text {
+        if c == b'"' || c == b'\\' {
+            escaped.push(b'\\');
+        }
+        escaped.push(c);
+    }
+    Cow::Owned(escaped)
+}
+
+/// Unescapes the contents of a VDF string, i.e. the inverse of [`escape`].
+///
+/// A backslash followed by any character stands for that character.
+pub fn unescape(text: &[u8]) -> Cow<'_, [u8]> {
+    if !text.contains(&b'\\') {
+        return Cow::Borrowed(text);
+    }
+    let mut unescaped = Vec::with_capacity(text.len());
+    let mut chars = text.iter();
+    while let Some(&c) = chars.next() {
+        match c {
+            b'\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::*;
+
+    /// A loginusers.vdf with three users.
+    const LOGINUSERS: &[u8] = include_bytes!("fixtures/loginusers.vdf");
+
+    /// Prefixes the source with a UTF-8 byte order mark.
+    fn with_bom(source: &[u8]) -> Vec<u8> {
+        [&[0xEF, 0xBB, 0xBF], source].concat()
+    }
+
+    #[test]
+    fn sort_by_recency() {
+        let document = scan_parse(LOGINUSERS).unwrap();
+        let mut users = LoginUser::collect_owned(&document).unwrap();
+        OwnedLoginUser::sort_by_recency(&mut users);
+        let usernames = users
+            .iter()
+            .map(|user| user.username.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(usernames, ["bob", "alice", "carol"]);
+        assert_eq!(users[2].timestamp, None);
+    }
+
+    #[test]
+    fn bom() {
+        let plain = scan_parse(LOGINUSERS).unwrap();
+        let bom = with_bom(LOGINUSERS);
+        let prefixed = scan_parse(&bom).unwrap();
+        assert_eq!(
+            LoginUser::collect_owned(&prefixed).unwrap(),
+            LoginUser::collect_owned(&plain).unwrap()
+        );
+        assert_eq!(prefixed.to_vdf(), plain.to_vdf());
+    }
+
+    #[test]
+    fn bom_read_scanner() {
+        let tokens = Scanner::new(LOGINUSERS)
+            .map(|token| token.unwrap().lexeme.to_vec())
+            .collect::<Vec<_>>();
+        let bom = with_bom(LOGINUSERS);
+        let read_tokens = ReadScanner::new(bom.as_slice())
+            .map(|token| token.unwrap().lexeme)
+            .collect::<Vec<_>>();
+        assert_eq!(read_tokens, tokens);
+    }
+
+    /// Generates a loginusers.vdf with the given number of users.
+    fn synthetic_loginusers(users: u64) -> Vec<u8> {
+        let mut source = String::from("\"users\"\n{\n");
+        for i in 0..users {
+            let _ = write!(source, "\t\"{}\"\n\t{{\n", 76561197960265728 + i);
+            for (key, value) in [
+                ("AccountName", format!("user_{i}")),
+                ("PersonaName", format!(r#"User \"{i}\""#)),
+                ("RememberPassword", "1".to_owned()),
+                ("AllowAutoLogin", "1".to_owned()),
+                ("MostRecent", "0".to_owned()),
+                ("Timestamp", (1_700_000_000 + i).to_string()),
+            ] {
+                let _ = writeln!(source, "\t\t\"{key}\"\t\t\"{value}\"");
+            }
+            source.push_str("\t}\n");
+        }
+        source.push_str("}\n");
+        source.into_bytes()
+    }
+
+    #[test]
+    fn parse_reader_large() {
+        let source = synthetic_loginusers(20_000);
+        assert!(source.len() > 3 << 20);
+
+        let read =
+            parse_reader(std::io::BufReader::with_capacity(4096, source.as_slice())).unwrap();
+        let scanned = scan_parse(&source).unwrap();
+        let users = LoginUser::collect_owned(&read).unwrap();
+        assert_eq!(users.len(), 20_000);
+        assert_eq!(users[19_999].username, "user_19999");
+        assert_eq!(users[19_999].nickname, r#"User \"19999\""#);
+        assert_eq!(users, LoginUser::collect_owned(&scanned).unwrap());
+        assert_eq!(read, scanned);
+        assert_eq!(read.to_vdf(), source);
+    }
+
+    #[test]
+    fn parse_reader_errors() {
+        assert!(matches!(
+            parse_reader(&b"\"key\" !"[..]),
+            Err(ReadParseError::ReadScanError(ReadScanError::Scan(
+                ScanError::UnexpectedToken(b'!')
+            )))
+        ));
+        assert!(matches!(
+            parse_reader(&b"\"key\" }"[..]),
+            Err(ReadParseError::ParseError(
+                ParseError::UnexpectedBraceRightNoMatch
+            ))
+        ));
+    }
+
+    #[test]
+    fn utf16_bom() {
+        assert_eq!(
+            scan_parse(&[0xFF, 0xFE, b'"', 0]),
+            Err(ScanParseError::ScanError(ScanError::Utf16))
+        );
+        assert!(matches!(
+            ReadScanner::new(&[0xFE, 0xFF, 0, b'"'][..]).next(),
+            Some(Err(ReadScanError::Scan(ScanError::Utf16)))
+        ));
+    }
+}
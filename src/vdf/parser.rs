@@ -1,5 +1,6 @@
-use super::Token;
+use super::{OwnedToken, Token, TokenType};
 use core::fmt::{self, Debug, Formatter};
+use std::{borrow::Cow, collections::HashMap};
 
 /// A [`Document`] element ID.
 #[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -12,33 +13,65 @@ impl Id {
 }
 
 /// A key value.
-#[derive(Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+///
+/// Non-exhaustive, since value types only some formats have may be added, like [`Value::Int`] was.
+#[derive(Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
+#[non_exhaustive]
 pub enum Value<'a> {
     /// A string value.
-    String(&'a [u8]),
+    ///
+    /// Parsed values borrow the source, values set after parsing are owned.
+    String(Cow<'a, [u8]>),
     /// Subkeys value.
     Subkeys(Id),
+    /// A 32-bit integer value, which only [binary](super::binary) documents have.
+    Int(i32),
+}
+
+impl<'a> Value<'a> {
+    /// Gets the value as an integer.
+    ///
+    /// String values are parsed as decimal integers. Returns [`None`] for subkeys, or strings that aren't integers.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::String(value) => std::str::from_utf8(value).ok()?.parse().ok(),
+            Self::Int(value) => Some(i64::from(*value)),
+            Self::Subkeys(_) => None,
+        }
+    }
+
+    /// Gets the value as a boolean.
+    ///
+    /// Per Valve's convention, `"0"` is false and any other value is true. Returns [`None`] for subkeys.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::String(value) => Some(value.as_ref() != b"0"),
+            Self::Int(value) => Some(*value != 0),
+            Self::Subkeys(_) => None,
+        }
+    }
 }
 
 impl<'a> Debug for Value<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match *self {
+        match self {
             Self::String(str) => f
                 .debug_tuple("String")
                 .field(&format_args!("{}", str.escape_ascii()))
                 .finish(),
             Self::Subkeys(id) => f.debug_tuple("Subkeys").field(&id).finish(),
+            Self::Int(int) => f.debug_tuple("Int").field(int).finish(),
         }
     }
 }
 
 /// A key-value pair.
-#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
 pub struct KeyValue<'a> {
     /// Where the key-value is specified.
     pub parent: Id,
     /// The key part.
-    pub key: &'a [u8],
+    pub key: Cow<'a, [u8]>,
     /// The value part.
     pub value: Value<'a>,
 }
@@ -46,7 +79,7 @@ pub struct KeyValue<'a> {
 /// A VDF document.
 #[derive(Hash, Default, Clone, PartialEq, PartialOrd, Eq, Ord)]
 #[repr(transparent)]
-pub struct Document<'a>(pub Vec<KeyValue<'a>>);
+pub struct Document<'a>(Vec<KeyValue<'a>>);
 
 impl<'a> Debug for Document<'a> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -62,8 +95,74 @@ impl<'a> Debug for Document<'a> {
 }
 
 impl<'a> Document<'a> {
+    /// Appends a key-value to the document.
+    #[inline]
+    pub(super) fn push(&mut self, key_value: KeyValue<'a>) {
+        self.0.push(key_value);
+    }
+
+    /// Iterates all the key-values in the document, in order of appearance.
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, KeyValue<'a>> {
+        self.0.iter()
+    }
+
+    /// Gets the number of key-values in the document, including nested ones.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks whether the document has no key-values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Gets the key-values directly under the given parent.
+    pub fn children(&self, parent: Id) -> impl Iterator<Item = &KeyValue<'a>> {
+        self.0.iter().filter(move |row| row.parent == parent)
+    }
+
+    /// Indexes the key-values by their parents, so that lookups only scan the key-values under the given parent
+    /// rather than the whole document.
+    ///
+    /// Worthwhile when making many lookups, e.g. a few per user in a large loginusers.vdf.
+    pub fn index(&self) -> DocumentIndex<'_, 'a> {
+        let mut children = HashMap::<Id, Vec<&KeyValue<'a>>>::new();
+        for row in &self.0 {
+            children.entry(row.parent).or_default().push(row);
+        }
+        DocumentIndex { children }
+    }
+
+    /// Gets the keys directly under the given parent.
+    pub fn children_keys(&self, parent: Id) -> impl Iterator<Item = &[u8]> {
+        self.children(parent).map(|row| row.key.as_ref())
+    }
+
+    /// Gets the parent of the given subkeys, i.e. where the key of the subkeys is specified.
+    ///
+    /// Returns [`None`] for the [root](Id::ROOT), or if there are no such subkeys.
+    pub fn parent_of(&self, id: Id) -> Option<Id> {
+        self.0
+            .iter()
+            .find(|row| row.value == Value::Subkeys(id))
+            .map(|row| row.parent)
+    }
+
+    /// Gets the key of the given subkeys.
+    ///
+    /// Returns [`None`] for the [root](Id::ROOT), or if there are no such subkeys.
+    pub fn key_of(&self, id: Id) -> Option<&[u8]> {
+        self.0
+            .iter()
+            .find(|row| row.value == Value::Subkeys(id))
+            .map(|row| row.key.as_ref())
+    }
+
     /// Gets the subkeys at the given path.
-    pub fn subkeys(&self, at: Id, key: &'a [u8]) -> Option<Id> {
+    pub fn subkeys(&self, at: Id, key: &[u8]) -> Option<Id> {
         let result = self.0.iter().find(|row| row.parent == at && row.key == key);
         match result {
             Some(KeyValue {
@@ -75,7 +174,7 @@ impl<'a> Document<'a> {
     }
 
     /// Gets the value at the given path.
-    pub fn value_str(&self, at: Id, name: &[u8]) -> Option<&'a [u8]> {
+    pub fn value_str(&self, at: Id, name: &[u8]) -> Option<&[u8]> {
         let result = self
             .0
             .iter()
@@ -84,10 +183,183 @@ impl<'a> Document<'a> {
             Some(KeyValue {
                 value: Value::String(sub),
                 ..
-            }) => Some(*sub),
+            }) => Some(sub),
             _ => None,
         }
     }
+
+    /// Gets the integer value at the given path.
+    ///
+    /// See [`Value::as_i64`]. Returns [`None`] if the value is missing, or isn't an integer.
+    pub fn value_i64(&self, at: Id, name: &[u8]) -> Option<i64> {
+        self.0
+            .iter()
+            .find(|row| row.parent == at && row.key == name)?
+            .value
+            .as_i64()
+    }
+
+    /// Gets the boolean value at the given path.
+    ///
+    /// See [`Value::as_bool`]. Returns [`None`] if the value is missing.
+    pub fn value_bool(&self, at: Id, name: &[u8]) -> Option<bool> {
+        self.0
+            .iter()
+            .find(|row| row.parent == at && row.key == name)?
+            .value
+            .as_bool()
+    }
+
+    /// Gets the value at the given path of keys, starting from the [root](Id::ROOT).
+    ///
+    /// Returns [`None`] if any key is missing, or if a key other than the last has a string value.
+    pub fn get_path(&self, path: &[&[u8]]) -> Option<&Value<'a>> {
+        let (last, init) = path.split_last()?;
+        let mut at = Id::ROOT;
+        for key in init {
+            at = self.subkeys(at, key)?;
+        }
+        self.0
+            .iter()
+            .find(|row| row.parent == at && row.key == *last)
+            .map(|row| &row.value)
+    }
+
+    /// Sets the string value at the given path.
+    ///
+    /// Updates the existing string value in place, or appends a new key-value if there is none.
+    /// The value is stored verbatim, i.e. it should already be escaped.
+    pub fn set_value(&mut self, at: Id, key: &[u8], value: &[u8]) {
+        let existing = self.0.iter_mut().find(|row| {
+            row.parent == at && row.key == key && matches!(row.value, Value::String(_))
+        });
+        match existing {
+            Some(row) => row.value = Value::String(Cow::Owned(value.to_vec())),
+            None => self.0.push(KeyValue {
+                parent: at,
+                key: Cow::Owned(key.to_vec()),
+                value: Value::String(Cow::Owned(value.to_vec())),
+            }),
+        }
+    }
+
+    /// Writes the document as VDF text, in Steam's layout of tab indentation and tab-separated key-values.
+    ///
+    /// Keys and string values are written verbatim, i.e. they should already be escaped, as parsed ones are.
+    pub fn to_vdf(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.0.len() * 32);
+        write_vdf(&self.index(), Id::ROOT, 0, &mut out);
+        out
+    }
+
+    /// Removes the key-value at the given path, along with its subkeys.
+    ///
+    /// Returns whether a key-value was found and removed.
+    pub fn remove(&mut self, at: Id, key: &[u8]) -> bool {
+        let Some(index) = self
+            .0
+            .iter()
+            .position(|row| row.parent == at && row.key == key)
+        else {
+            return false;
+        };
+        if let Value::Subkeys(sub) = self.0.remove(index).value {
+            let mut orphans = vec![sub];
+            while let Some(parent) = orphans.pop() {
+                self.0.retain(|row| {
+                    if row.parent != parent {
+                        return true;
+                    }
+                    if let Value::Subkeys(sub) = row.value {
+                        orphans.push(sub);
+                    }
+                    false
+                });
+            }
+        }
+        true
+    }
+}
+
+/// Writes the key-values under the given parent, and their subkeys, indented `depth` levels, for
+/// [`Document::to_vdf`].
+fn write_vdf(index: &DocumentIndex, parent: Id, depth: usize, out: &mut Vec<u8>) {
+    for row in index.children(parent) {
+        out.extend(std::iter::repeat_n(b'\t', depth));
+        out.push(b'"');
+        out.extend_from_slice(&row.key);
+        out.push(b'"');
+        match &row.value {
+            Value::String(value) => {
+                out.extend_from_slice(b"\t\t\"");
+                out.extend_from_slice(value);
+                out.extend_from_slice(b"\"\n");
+            }
+            Value::Int(value) => out.extend_from_slice(format!("\t\t\"{value}\"\n").as_bytes()),
+            Value::Subkeys(sub) => {
+                out.push(b'\n');
+                out.extend(std::iter::repeat_n(b'\t', depth));
+                out.extend_from_slice(b"{\n");
+                write_vdf(index, *sub, depth + 1, out);
+                out.extend(std::iter::repeat_n(b'\t', depth));
+                out.extend_from_slice(b"}\n");
+            }
+        }
+    }
+}
+
+/// A [`Document`]'s key-values indexed by their parents, see [`Document::index`].
+#[derive(Debug, Clone)]
+pub struct DocumentIndex<'d, 'a> {
+    children: HashMap<Id, Vec<&'d KeyValue<'a>>>,
+}
+
+impl<'d, 'a> DocumentIndex<'d, 'a> {
+    /// Gets the key-values directly under the given parent, like [`Document::children`].
+    pub fn children(&self, parent: Id) -> impl Iterator<Item = &'d KeyValue<'a>> + '_ {
+        self.children.get(&parent).into_iter().flatten().copied()
+    }
+
+    /// Gets the key-value of the given key directly under the given parent.
+    fn get(&self, at: Id, key: &[u8]) -> Option<&'d KeyValue<'a>> {
+        self.children(at).find(|row| row.key == key)
+    }
+
+    /// Gets the subkeys at the given path, like [`Document::subkeys`].
+    pub fn subkeys(&self, at: Id, key: &[u8]) -> Option<Id> {
+        match self.get(at, key)?.value {
+            Value::Subkeys(sub) => Some(sub),
+            _ => None,
+        }
+    }
+
+    /// Gets the value at the given path, like [`Document::value_str`].
+    pub fn value_str(&self, at: Id, name: &[u8]) -> Option<&'d [u8]> {
+        match &self.get(at, name)?.value {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Gets the integer value at the given path, like [`Document::value_i64`].
+    pub fn value_i64(&self, at: Id, name: &[u8]) -> Option<i64> {
+        self.get(at, name)?.value.as_i64()
+    }
+
+    /// Gets the boolean value at the given path, like [`Document::value_bool`].
+    pub fn value_bool(&self, at: Id, name: &[u8]) -> Option<bool> {
+        self.get(at, name)?.value.as_bool()
+    }
+}
+
+impl<'d, 'a> IntoIterator for &'d Document<'a> {
+    type Item = &'d KeyValue<'a>;
+    type IntoIter = std::slice::Iter<'d, KeyValue<'a>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 /// Parse error.
@@ -102,8 +374,14 @@ pub enum Error {
     /// Unexpected EOF after key name.
     #[error("expected key value after key name but reached EOF")]
     ExpectedKeyValueAfterKeyName,
+    /// The subkeys are nested deeper than the maximum depth.
+    #[error("subkeys are nested too deeply")]
+    MaxDepthExceeded,
 }
 
+/// The default maximum nesting depth of subkeys, see [`parse_with_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
 /// Removes the first and last characters.
 ///
 /// Useful to remove surrounding characters like quotes.
@@ -111,6 +389,39 @@ fn unsurround(s: &[u8]) -> &[u8] {
     &s[1..s.len() - 1]
 }
 
+/// A token the parser consumes, i.e. a [`Token`] that borrows the source, or an [`OwnedToken`].
+pub trait ParseToken<'a> {
+    /// Gets the token's type.
+    fn token_type(&self) -> TokenType;
+
+    /// Gets the text of a [string](TokenType::String) token, without its quotes.
+    fn into_text(self) -> Cow<'a, [u8]>;
+}
+
+impl<'a> ParseToken<'a> for Token<'a> {
+    #[inline]
+    fn token_type(&self) -> TokenType {
+        self.r#type
+    }
+
+    #[inline]
+    fn into_text(self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(unsurround(self.lexeme))
+    }
+}
+
+impl ParseToken<'static> for OwnedToken {
+    #[inline]
+    fn token_type(&self) -> TokenType {
+        self.r#type
+    }
+
+    #[inline]
+    fn into_text(self) -> Cow<'static, [u8]> {
+        Cow::Owned(unsurround(&self.lexeme).to_vec())
+    }
+}
+
 #[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
 enum ParseOneTerminal {
     BlockEnd,
@@ -118,62 +429,364 @@ enum ParseOneTerminal {
     Yield,
 }
 
-/// Parses a single element.
+/// Parses a single element, allowing its subkeys to nest up to `depth` levels.
 fn parse_one<'a>(
-    tokens: &mut impl Iterator<Item = Token<'a>>,
+    tokens: &mut impl Iterator<Item = impl ParseToken<'a>>,
     document: &mut Document<'a>,
     parent: Id,
     brace_terminal: bool,
+    depth: usize,
 ) -> Result<ParseOneTerminal, Error> {
-    let Some(head) = tokens.next() else { return Ok(ParseOneTerminal::Eof) };
-    match head.r#type {
-        super::TokenType::BraceLeft => Err(Error::UnexpectedBraceLeftNoName),
-        super::TokenType::BraceRight => {
+    let Some(head) = tokens.next() else {
+        return Ok(ParseOneTerminal::Eof);
+    };
+    match head.token_type() {
+        TokenType::BraceLeft => Err(Error::UnexpectedBraceLeftNoName),
+        TokenType::BraceRight => {
             if brace_terminal {
                 Ok(ParseOneTerminal::BlockEnd)
             } else {
                 Err(Error::UnexpectedBraceRightNoMatch)
             }
         }
-        super::TokenType::String => {
-            let name = head;
-            let Some(value ) = tokens.next() else { return Err(Error::ExpectedKeyValueAfterKeyName) };
-            match value.r#type {
-                super::TokenType::String => {
+        TokenType::String => {
+            let name = head.into_text();
+            let Some(value) = tokens.next() else {
+                return Err(Error::ExpectedKeyValueAfterKeyName);
+            };
+            match value.token_type() {
+                TokenType::String => {
                     document.0.push(KeyValue {
                         parent,
-                        key: unsurround(name.lexeme),
-                        value: Value::String(unsurround(value.lexeme)),
+                        key: name,
+                        value: Value::String(value.into_text()),
                     });
                     Ok(ParseOneTerminal::Yield)
                 }
-                super::TokenType::BraceLeft => {
-                    let sub_parent = Id(name.lexeme.as_ptr() as usize);
+                TokenType::BraceLeft => {
+                    let Some(depth) = depth.checked_sub(1) else {
+                        return Err(Error::MaxDepthExceeded);
+                    };
+                    // the index of the key-value of the subkeys is unique, unlike addresses of owned keys, which
+                    // may be reused.
+                    let sub_parent = Id(document.0.len());
                     document.0.push(KeyValue {
                         parent,
-                        key: unsurround(name.lexeme),
+                        key: name,
                         value: Value::Subkeys(sub_parent),
                     });
                     loop {
-                        let piece = parse_one(tokens, document, sub_parent, true)?;
+                        let piece = parse_one(tokens, document, sub_parent, true, depth)?;
                         if piece == ParseOneTerminal::BlockEnd {
                             break Ok(ParseOneTerminal::Yield);
                         }
                     }
                 }
-                super::TokenType::BraceRight => Err(Error::UnexpectedBraceRightNoMatch),
+                TokenType::BraceRight => Err(Error::UnexpectedBraceRightNoMatch),
             }
         }
     }
 }
 
-/// Parses a [`Document`].
-pub fn parse<'a>(mut tokens: impl Iterator<Item = Token<'a>>) -> Result<Document<'a>, Error> {
+/// Parses a [`Document`], allowing subkeys to nest up to [`DEFAULT_MAX_DEPTH`] levels.
+pub fn parse<'a>(tokens: impl Iterator<Item = impl ParseToken<'a>>) -> Result<Document<'a>, Error> {
+    parse_with_depth(tokens, DEFAULT_MAX_DEPTH)
+}
+
+/// Parses a [`Document`], allowing subkeys to nest up to `max_depth` levels.
+///
+/// Fails with [`Error::MaxDepthExceeded`] on deeper nesting, rather than overflowing the stack.
+pub fn parse_with_depth<'a>(
+    mut tokens: impl Iterator<Item = impl ParseToken<'a>>,
+    max_depth: usize,
+) -> Result<Document<'a>, Error> {
     let mut document = Document::default();
     loop {
-        if parse_one(&mut tokens, &mut document, Id::ROOT, false)? != ParseOneTerminal::Eof {
+        if parse_one(&mut tokens, &mut document, Id::ROOT, false, max_depth)?
+            == ParseOneTerminal::Eof
+        {
             break;
         }
     }
     Ok(document)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vdf::{scan_parse, LoginUser, ScanParseError, Scanner};
+
+    fn string(value: &[u8]) -> Value<'_> {
+        Value::String(Cow::Borrowed(value))
+    }
+
+    #[test]
+    fn empty() {
+        let document = scan_parse(b"").unwrap();
+        assert!(document.is_empty());
+    }
+
+    #[test]
+    fn one_entry() {
+        let document = scan_parse(br#""key" "value""#).unwrap();
+        assert_eq!(document.len(), 1);
+        assert_eq!(document.value_str(Id::ROOT, b"key"), Some(&b"value"[..]));
+    }
+
+    #[test]
+    fn many_entries() {
+        let document = scan_parse(b"\"a\" \"1\"\n\"b\" \"2\"\n\"c\" \"3\"").unwrap();
+        let entries = document
+            .iter()
+            .map(|kv| (kv.parent, kv.key.as_ref(), kv.value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (Id::ROOT, &b"a"[..], string(b"1")),
+                (Id::ROOT, &b"b"[..], string(b"2")),
+                (Id::ROOT, &b"c"[..], string(b"3")),
+            ]
+        );
+    }
+
+    #[test]
+    fn into_iter() {
+        let document = scan_parse(b"\"outer\" { \"inner\" \"1\" } \"flat\" \"2\"").unwrap();
+        let mut iterated = Vec::new();
+        for kv in &document {
+            iterated.push(kv);
+        }
+        assert_eq!(iterated, document.iter().collect::<Vec<_>>());
+        assert_eq!(iterated.len(), 3);
+    }
+
+    #[test]
+    fn nested_then_flat() {
+        let document =
+            scan_parse(b"\"outer\"\n{\n\t\"inner\"\t\t\"1\"\n}\n\"flat\"\t\t\"2\"\n").unwrap();
+        let outer = document.subkeys(Id::ROOT, b"outer").unwrap();
+        assert_eq!(document.value_str(outer, b"inner"), Some(&b"1"[..]));
+        assert_eq!(document.value_str(Id::ROOT, b"flat"), Some(&b"2"[..]));
+        assert_eq!(document.value_str(outer, b"flat"), None);
+        assert_eq!(document.children(Id::ROOT).count(), 2);
+    }
+
+    #[test]
+    fn empty_block() {
+        let document = scan_parse(b"\"settings\" { }").unwrap();
+        let settings = document.subkeys(Id::ROOT, b"settings").unwrap();
+        assert_eq!(document.children(settings).count(), 0);
+        assert_eq!(document.to_vdf(), b"\"settings\"\n{\n}\n");
+    }
+
+    #[test]
+    fn empty_blocks_among_non_empty() {
+        let source = b"\"root\"\n{\n\t\"a\"\n\t{\n\t}\n\t\"b\"\n\t{\n\t\t\"key\"\t\t\"value\"\n\t\t\"c\"\n\t\t{\n\t\t}\n\t}\n\t\"d\"\n\t{\n\t}\n}\n";
+        let document = scan_parse(source).unwrap();
+        let root = document.subkeys(Id::ROOT, b"root").unwrap();
+        assert_eq!(
+            document.children_keys(root).collect::<Vec<_>>(),
+            [&b"a"[..], b"b", b"d"]
+        );
+        let a = document.subkeys(root, b"a").unwrap();
+        let b = document.subkeys(root, b"b").unwrap();
+        let c = document.subkeys(b, b"c").unwrap();
+        let d = document.subkeys(root, b"d").unwrap();
+        assert_eq!(document.children(a).count(), 0);
+        assert_eq!(document.value_str(b, b"key"), Some(&b"value"[..]));
+        assert_eq!(document.children(c).count(), 0);
+        assert_eq!(document.children(d).count(), 0);
+        assert_eq!(document.to_vdf(), source);
+    }
+
+    /// A Steam registry.vdf, as on Linux.
+    const REGISTRY: &[u8] = include_bytes!("fixtures/registry.vdf");
+
+    #[test]
+    fn registry_round_trip() {
+        let document = scan_parse(REGISTRY).unwrap();
+        let written = document.to_vdf();
+        assert_eq!(written, REGISTRY);
+        assert_eq!(scan_parse(&written).unwrap(), document);
+    }
+
+    #[test]
+    fn registry_set_value_round_trip() {
+        let mut document = scan_parse(REGISTRY).unwrap();
+        let steam = [&b"Registry"[..], b"HKCU", b"Software", b"Valve", b"Steam"]
+            .iter()
+            .try_fold(Id::ROOT, |at, key| document.subkeys(at, key))
+            .unwrap();
+        let original = document.clone();
+        document.set_value(steam, b"AutoLoginUser", b"bob");
+        document.set_value(steam, b"LastGameNameUsed", br#"Bob \"B\""#);
+
+        let written = document.to_vdf();
+        let reparsed = scan_parse(&written).unwrap();
+        assert_eq!(reparsed, document);
+        assert_eq!(
+            reparsed.value_str(steam, b"AutoLoginUser"),
+            Some(&b"bob"[..])
+        );
+        assert_eq!(
+            reparsed.value_str(steam, b"LastGameNameUsed"),
+            Some(&br#"Bob \"B\""#[..])
+        );
+        // everything else is left as is.
+        fn unchanged<'a>(document: &'a Document) -> Vec<&'a KeyValue<'a>> {
+            document
+                .0
+                .iter()
+                .filter(|row| *row.key != *b"AutoLoginUser" && *row.key != *b"LastGameNameUsed")
+                .collect()
+        }
+        assert_eq!(unchanged(&reparsed), unchanged(&original));
+    }
+
+    #[test]
+    fn remove() {
+        let mut document = scan_parse(LOGIN_USERS).unwrap();
+        let users = document.subkeys(Id::ROOT, b"users").unwrap();
+        let bob = document.subkeys(users, b"76561197960287931").unwrap();
+        let len = document.len();
+
+        assert!(document.remove(bob, b"Timestamp"));
+        assert_eq!(document.value_str(bob, b"Timestamp"), None);
+        assert_eq!(document.len(), len - 1);
+        assert!(!document.remove(bob, b"Timestamp"));
+        assert!(!document.remove(users, b"missing"));
+        assert_eq!(document.len(), len - 1);
+
+        assert!(document.remove(users, b"76561197960287930"));
+        assert_eq!(document.subkeys(users, b"76561197960287930"), None);
+        assert_eq!(document.children(users).count(), 2);
+        assert_eq!(document.len(), len - 1 - 9);
+
+        let written = document.to_vdf();
+        let reparsed = scan_parse(&written).unwrap();
+        assert_eq!(reparsed.to_vdf(), written);
+        let users = reparsed.subkeys(Id::ROOT, b"users").unwrap();
+        assert_eq!(
+            reparsed.children_keys(users).collect::<Vec<_>>(),
+            [&b"76561197960287931"[..], b"76561197960287932"]
+        );
+        let bob = reparsed.subkeys(users, b"76561197960287931").unwrap();
+        assert_eq!(reparsed.value_str(bob, b"AccountName"), Some(&b"bob"[..]));
+        assert_eq!(reparsed.value_str(bob, b"Timestamp"), None);
+    }
+
+    #[test]
+    fn value_i64() {
+        let document =
+            scan_parse(b"\"int\" \"42\" \"negative\" \"-7\" \"text\" \"4x\" \"sub\" { }").unwrap();
+        assert_eq!(document.value_i64(Id::ROOT, b"int"), Some(42));
+        assert_eq!(document.value_i64(Id::ROOT, b"negative"), Some(-7));
+        assert_eq!(document.value_i64(Id::ROOT, b"text"), None);
+        assert_eq!(document.value_i64(Id::ROOT, b"sub"), None);
+        assert_eq!(document.value_i64(Id::ROOT, b"missing"), None);
+        let index = document.index();
+        assert_eq!(index.value_i64(Id::ROOT, b"int"), Some(42));
+        assert_eq!(index.value_i64(Id::ROOT, b"text"), None);
+        assert_eq!(Value::Int(-3).as_i64(), Some(-3));
+    }
+
+    #[test]
+    fn value_bool() {
+        let document = scan_parse(
+            b"\"zero\" \"0\" \"one\" \"1\" \"other\" \"yes\" \"empty\" \"\" \"sub\" { }",
+        )
+        .unwrap();
+        assert_eq!(document.value_bool(Id::ROOT, b"zero"), Some(false));
+        assert_eq!(document.value_bool(Id::ROOT, b"one"), Some(true));
+        assert_eq!(document.value_bool(Id::ROOT, b"other"), Some(true));
+        assert_eq!(document.value_bool(Id::ROOT, b"empty"), Some(true));
+        assert_eq!(document.value_bool(Id::ROOT, b"sub"), None);
+        assert_eq!(document.value_bool(Id::ROOT, b"missing"), None);
+        let index = document.index();
+        assert_eq!(index.value_bool(Id::ROOT, b"zero"), Some(false));
+        assert_eq!(index.value_bool(Id::ROOT, b"one"), Some(true));
+        assert_eq!(Value::Int(0).as_bool(), Some(false));
+        assert_eq!(Value::Int(2).as_bool(), Some(true));
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let source = b"\"k\" {".repeat(10_000);
+        assert_eq!(
+            scan_parse(&source),
+            Err(ScanParseError::ParseError(Error::MaxDepthExceeded))
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        let source = b"\"a\" { \"b\" { \"c\" \"1\" } }";
+        let tokens = || Scanner::new(source).map(Result::unwrap);
+        assert!(parse_with_depth(tokens(), 2).is_ok());
+        assert_eq!(parse_with_depth(tokens(), 1), Err(Error::MaxDepthExceeded));
+    }
+
+    #[test]
+    fn login_users() {
+        let source = br#""users"
+{
+	"76561197960287930"
+	{
+		"AccountName"		"alice"
+		"PersonaName"		"Alice"
+		"RememberPassword"		"1"
+		"MostRecent"		"1"
+		"Timestamp"		"1700000000"
+	}
+	"76561197960287931"
+	{
+		"AccountName"		"bob"
+		"PersonaName"		"Bob"
+		"AllowAutoLogin"		"0"
+	}
+}
+"#;
+        let document = scan_parse(source).unwrap();
+        let users = LoginUser::from_vdf(&document)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(users.len(), 2);
+
+        assert_eq!(users[0].steamid, b"76561197960287930");
+        assert_eq!(users[0].username, b"alice");
+        assert_eq!(users[0].nickname, b"Alice");
+        assert!(users[0].remember_password);
+        assert!(users[0].most_recent);
+        assert!(!users[0].allow_auto_login);
+        assert_eq!(users[0].timestamp, Some(1_700_000_000));
+
+        assert_eq!(users[1].steamid, b"76561197960287931");
+        assert_eq!(users[1].username, b"bob");
+        assert_eq!(users[1].nickname, b"Bob");
+        assert!(!users[1].remember_password);
+        assert!(!users[1].most_recent);
+        assert_eq!(users[1].timestamp, None);
+    }
+
+    /// A Steam loginusers.vdf.
+    const LOGIN_USERS: &[u8] = include_bytes!("fixtures/loginusers.vdf");
+
+    #[test]
+    fn parent_and_key_of() {
+        let document = scan_parse(LOGIN_USERS).unwrap();
+        let carol = document
+            .iter()
+            .find(|row| *row.key == *b"AccountName" && row.value == string(b"carol"))
+            .unwrap();
+
+        let user = carol.parent;
+        assert_eq!(document.key_of(user), Some(&b"76561197960287932"[..]));
+        let users = document.parent_of(user).unwrap();
+        assert_eq!(document.key_of(users), Some(&b"users"[..]));
+        assert_eq!(document.parent_of(users), Some(Id::ROOT));
+        assert_eq!(document.parent_of(Id::ROOT), None);
+        assert_eq!(document.key_of(Id::ROOT), None);
+    }
+}
@@ -0,0 +1,148 @@
+//! Scanning VDF from a reader, without buffering the whole source.
+
+use std::io::{self, BufRead};
+
+use super::{ScanError, Token, TokenType};
+
+/// A VDF token that owns its lexeme, as scanned by a [`ReadScanner`].
+#[derive(Debug, Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
+pub struct OwnedToken {
+    /// The token [type](TokenType).
+    pub r#type: TokenType,
+    /// The token's text.
+    pub lexeme: Vec<u8>,
+}
+
+impl OwnedToken {
+    /// Borrows the token as a [`Token`].
+    #[inline]
+    pub fn as_token(&self) -> Token<'_> {
+        Token {
+            r#type: self.r#type,
+            lexeme: &self.lexeme,
+        }
+    }
+}
+
+/// Failure to scan a VDF source from a reader.
+#[derive(Debug, thiserror::Error)]
+pub enum ReadScanError {
+    /// Failed to read the source.
+    #[error("failed to read: {0}")]
+    Io(#[from] io::Error),
+    /// Failed to scan the source.
+    #[error("lexing error: {0}")]
+    Scan(#[from] ScanError),
+}
+
+/// A [`Scanner`](super::Scanner) over a reader.
+///
+/// Unlike the [`Scanner`](super::Scanner), whose [`Token`]s borrow the source, this only buffers as much of the source
+/// as the reader does, at the cost of allocating a lexeme per [`OwnedToken`]. Prefer the [`Scanner`](super::Scanner)
+/// for sources that fit comfortably in memory, which Steam's VDF files typically do.
+///
+/// [`parse_reader`](super::parse_reader) parses its tokens into a [`Document`](super::Document).
+#[derive(Debug)]
+pub struct ReadScanner<R> {
+    reader: R,
+    /// Whether the start of the source (and its byte order mark) was handled.
+    started: bool,
+    /// Whether scanning ended, by reaching the end of the source or an error.
+    finished: bool,
+}
+
+impl<R: BufRead> ReadScanner<R> {
+    /// Creates a new [`ReadScanner`].
+    #[inline]
+    pub const fn new(reader: R) -> Self {
+        Self {
+            reader,
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// Peeks the next byte.
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    /// Reads the next byte.
+    fn advance(&mut self) -> io::Result<Option<u8>> {
+        let c = self.peek()?;
+        if c.is_some() {
+            self.reader.consume(1);
+        }
+        Ok(c)
+    }
+
+    /// Skips a leading UTF-8 byte order mark, or fails on a UTF-16 one.
+    fn start(&mut self) -> Result<(), ReadScanError> {
+        let head = self.reader.fill_buf()?;
+        match head {
+            [0xEF, 0xBB, 0xBF, ..] => self.reader.consume(3),
+            [0xFF, 0xFE, ..] | [0xFE, 0xFF, ..] => return Err(ScanError::Utf16.into()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Scans the rest of a string, after its opening quote.
+    fn string_tail(&mut self) -> Result<OwnedToken, ReadScanError> {
+        let mut lexeme = vec![b'"'];
+        loop {
+            match self.advance()? {
+                Some(b'"') => {
+                    lexeme.push(b'"');
+                    break Ok(OwnedToken {
+                        r#type: TokenType::String,
+                        lexeme,
+                    });
+                }
+                Some(b'\\') => {
+                    lexeme.push(b'\\');
+                    lexeme.extend(self.advance()?);
+                }
+                Some(c) => lexeme.push(c),
+                None => break Err(ScanError::UnterminatedString.into()),
+            }
+        }
+    }
+
+    /// Scans the next token.
+    fn scan(&mut self) -> Result<Option<OwnedToken>, ReadScanError> {
+        if !self.started {
+            self.started = true;
+            self.start()?;
+        }
+        let token = |r#type, c| {
+            Ok(Some(OwnedToken {
+                r#type,
+                lexeme: vec![c],
+            }))
+        };
+        loop {
+            match self.advance()? {
+                Some(c) if c.is_ascii_whitespace() => {}
+                Some(b'"') => break self.string_tail().map(Some),
+                Some(b'{') => break token(TokenType::BraceLeft, b'{'),
+                Some(b'}') => break token(TokenType::BraceRight, b'}'),
+                Some(c) => break Err(ScanError::UnexpectedToken(c).into()),
+                None => break Ok(None),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ReadScanner<R> {
+    type Item = Result<OwnedToken, ReadScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.scan().transpose();
+        self.finished = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
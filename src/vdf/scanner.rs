@@ -31,12 +31,18 @@ pub struct Scanner<'a> {
 
 impl<'a> Scanner<'a> {
     /// Creates a new [`Scanner`].
+    ///
+    /// A leading UTF-8 byte order mark is skipped.
     #[inline]
     pub const fn new(source: &'a [u8]) -> Self {
+        let start = match source {
+            [0xEF, 0xBB, 0xBF, ..] => 3,
+            _ => 0,
+        };
         Self {
             source,
-            start: 0,
-            current: 0,
+            start,
+            current: start,
         }
     }
 
@@ -88,6 +94,9 @@ pub enum Error {
     /// Unterminated string literal.
     #[error("unterminated string")]
     UnterminatedString,
+    /// The source starts with a UTF-16 byte order mark, but only UTF-8 is supported.
+    #[error("the file is encoded in UTF-16, only UTF-8 is supported")]
+    Utf16,
 }
 
 impl<'a> Iterator for Scanner<'a> {
@@ -95,6 +104,10 @@ impl<'a> Iterator for Scanner<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
+        if self.current == 0 && matches!(self.source, [0xFF, 0xFE, ..] | [0xFE, 0xFF, ..]) {
+            self.current = self.source.len();
+            return Some(Err(Error::Utf16));
+        }
         self.start = self.current;
         let head = self.advance();
         // TODO: comments?
@@ -1,6 +1,5 @@
 #include <stdint.h>
 #include <Windows.h>
-#include <Psapi.h>
 #include <Shlwapi.h>
 #include <ctype.h>
 #include <stdio.h>
@@ -15,6 +14,9 @@ typedef enum {
     ENUM_PROCESSES,
     KILL_STEAM,
     OPEN_VDF,
+    READ_VERSION,
+    WATCH_STEAM_REGISTRY,
+    FIND_STEAM,
 } phase_t;
 
 typedef struct {
@@ -32,6 +34,11 @@ typedef struct {
     wchar_t path[MAX_PATH];
 } steam_t;
 
+static void steam_normalize_path(steam_t *steam) {
+    for (size_t i = 0; i < steam->len; i++)
+        steam->path[i] = steam->path[i] == '/' ? '\\' : towlower(steam->path[i]);
+}
+
 result_t steam_init(steam_t *steam) {
     DWORD size = sizeof(steam->path);
     const LSTATUS status = RegGetValueW(
@@ -45,8 +52,20 @@ result_t steam_init(steam_t *steam) {
     );
     if (status != ERROR_SUCCESS) return (result_t){READ_STEAM_REGISTRY, status};
     steam->len = (wchar_t)(size / sizeof(wchar_t) - /* NUL */ 1);
-    for (size_t i = 0; i < steam->len; i++)
-        steam->path[i] = steam->path[i] == '/' ? '\\' : towlower(steam->path[i]);
+    steam_normalize_path(steam);
+    return SUCCESS;
+}
+
+/// note: path is the path to the steam executable, with len excluding NUL terminator (which isn't required).
+result_t steam_init_path(steam_t *steam, const wchar_t *path, size_t len) {
+    if (len >= MAX_PATH) return (result_t){FIND_STEAM, ERROR_FILENAME_EXCED_RANGE};
+    memcpy(steam->path, path, len * sizeof(wchar_t));
+    steam->path[len] = L'\0';
+    steam->len = (wchar_t)len;
+    const DWORD attributes = GetFileAttributesW(steam->path);
+    if (attributes == INVALID_FILE_ATTRIBUTES) return FAILURE(FIND_STEAM);
+    if (attributes & FILE_ATTRIBUTE_DIRECTORY) return (result_t){FIND_STEAM, ERROR_FILE_NOT_FOUND};
+    steam_normalize_path(steam);
     return SUCCESS;
 }
 
@@ -105,6 +124,17 @@ result_t steam_launch_fast(steam_t const *steam) {
     return result;
 }
 
+/// note: args needs to be writable, and to begin with the (quoted) executable path.
+result_t steam_launch_with_args(steam_t const *steam, wchar_t *args) {
+    PROCESS_INFORMATION process;
+    const result_t result = steam_launch_args(steam, args, &process);
+    if (result.type == OK) {
+        CloseHandle(process.hThread);
+        CloseHandle(process.hProcess);
+    }
+    return result;
+}
+
 /// @return dir length, excluding NUL
 static size_t steam_dir_lowercase(steam_t const *steam, wchar_t out[MAX_PATH]) {
     const size_t dir_len = steam->len - (sizeof("steam.exe") - /* NUL */ 1);
@@ -113,77 +143,6 @@ static size_t steam_dir_lowercase(steam_t const *steam, wchar_t out[MAX_PATH]) {
     return dir_len;
 }
 
-static uint8_t steam_path_is_ancestor(wchar_t* path, size_t path_len, const wchar_t* dir_lowercase, size_t dir_len) {
-    if (path_len < dir_len) return 0;
-    for (size_t i = dir_len - 1; i != ((size_t)-1); i--)
-        if (towlower(path[i]) != dir_lowercase[i]) return 0;
-    return 1;
-}
-
-typedef struct {
-    DWORD pids[5000];
-    uint16_t len;
-    uint16_t index;
-    const wchar_t *dir;
-    size_t dir_len;
-} steam_process_iter_t;
-
-DWORD steam_process_iter_init(steam_process_iter_t *iter, wchar_t* steam_dir, size_t steam_dir_len) {
-    DWORD bytes_len = 0;
-    if (!EnumProcesses(iter->pids, sizeof(iter->pids), &bytes_len))
-        return GetLastError();
-    iter->index = 0;
-    iter->len = (uint16_t)(bytes_len / sizeof(DWORD));
-    iter->dir = steam_dir;
-    iter->dir_len = steam_dir_len;
-    return ERROR_SUCCESS;
-}
-
-typedef struct {
-    DWORD pid;
-    HANDLE handle;
-} steam_process_t;
-
-// note: remember to close the handle after use
-steam_process_t steam_process_iter_next(steam_process_iter_t *iter) {
-    for (; iter->index < iter->len; iter->index++) {
-        const DWORD pid = iter->pids[iter->index];
-        const HANDLE process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_TERMINATE, FALSE, pid);
-        if (process == NULL) continue;
-        wchar_t path[MAX_PATH];
-        DWORD path_len = sizeof(path) / sizeof(wchar_t);
-        if (!QueryFullProcessImageNameW(process, 0, path, &path_len)) goto next_process;
-        if (steam_path_is_ancestor(path, path_len, iter->dir, iter->dir_len)) {
-            iter->index++;
-            return (steam_process_t){pid,process};
-        }
-        next_process:
-        CloseHandle(process);
-    }
-    return (steam_process_t){0,0};
-}
-
-result_t steam_kill(steam_t const *steam, uint8_t killed) {
-    wchar_t dir[MAX_PATH];
-    const size_t dir_len = steam_dir_lowercase(steam, dir);
-
-    steam_process_iter_t iter;
-    DWORD iter_result = steam_process_iter_init(&iter, dir, dir_len);
-    if (iter_result != ERROR_SUCCESS) return (result_t){ENUM_PROCESSES, iter_result};
-
-    for (steam_process_t process = steam_process_iter_next(&iter); process.pid != 0; process = steam_process_iter_next(&iter)) {
-        if (TerminateProcess(process.handle, EXIT_SUCCESS)) {
-            CloseHandle(process.handle);
-            killed = 1;
-        } else {
-            CloseHandle(process.handle);
-            return FAILURE(KILL_STEAM);
-        }
-    }
-
-    return SUCCESS;
-}
-
 /// ensure username is lowercase and username_len includes NUL terminator
 result_t steam_set_auto_login_user(const char* username, uint8_t username_len) {
     LSTATUS status = RegSetKeyValueA(
@@ -196,6 +155,14 @@ result_t steam_set_auto_login_user(const char* username, uint8_t username_len) {
     return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){WRITE_STEAM_REGISTRY, status};
 }
 
+/// succeeds if there's no auto-login user to begin with.
+result_t steam_clear_auto_login_user(void) {
+    LSTATUS status = RegDeleteKeyValueA(HKEY_CURRENT_USER, "SOFTWARE\\Valve\\Steam", "AutoLoginUser");
+    return (status == ERROR_SUCCESS || status == ERROR_FILE_NOT_FOUND)
+        ? SUCCESS
+        : (result_t){WRITE_STEAM_REGISTRY, status};
+}
+
 /// ensure username is lowercase and username_len includes NUL terminator
 result_t steam_get_auto_login_user(char* username, uint8_t *username_len) {
     DWORD len = *username_len;
@@ -208,27 +175,147 @@ result_t steam_get_auto_login_user(char* username, uint8_t *username_len) {
         username,
         &len);
     *username_len = (uint8_t)len;
+    return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){READ_STEAM_REGISTRY, status};
+}
+
+/// reads the auto-login user's value as is, like steam_get_auto_login_user, but of any length.
+/// note: size is in bytes, including the NUL terminator. if data is too small, fails with ERROR_MORE_DATA, setting size
+/// to the required size.
+result_t steam_get_auto_login_user_raw(char *data, DWORD *size) {
+    LSTATUS status = RegGetValueA(
+        HKEY_CURRENT_USER,
+        "SOFTWARE\\Valve\\Steam",
+        "AutoLoginUser",
+        RRF_RT_REG_SZ,
+        NULL,
+        data,
+        size);
+    return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){READ_STEAM_REGISTRY, status};
+}
+
+/// note: name must be NUL terminated, and size is in bytes, including the NUL terminator. if data is too small, fails
+/// with ERROR_MORE_DATA, setting size to the required size.
+result_t steam_registry_get_string(const wchar_t *name, wchar_t *data, DWORD *size) {
+    LSTATUS status = RegGetValueW(
+        HKEY_CURRENT_USER,
+        L"SOFTWARE\\Valve\\Steam",
+        name,
+        RRF_RT_REG_SZ,
+        NULL,
+        data,
+        size);
+    return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){READ_STEAM_REGISTRY, status};
+}
+
+/// note: name and data must be NUL terminated, and size is data's size in bytes, including the NUL terminator.
+result_t steam_registry_set_string(const wchar_t *name, const wchar_t *data, DWORD size) {
+    LSTATUS status = RegSetKeyValueW(
+        HKEY_CURRENT_USER,
+        L"SOFTWARE\\Valve\\Steam",
+        name,
+        REG_SZ,
+        data,
+        size);
     return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){WRITE_STEAM_REGISTRY, status};
 }
 
-result_t steam_is_running(const steam_t* steam, uint8_t *is_running) {
-    *is_running = 0;
-    wchar_t dir[MAX_PATH];
-    const size_t dir_len = steam_dir_lowercase(steam, dir);
+/// note: name must be NUL terminated.
+result_t steam_registry_get_dword(const wchar_t *name, DWORD *value) {
+    DWORD size = sizeof(*value);
+    LSTATUS status = RegGetValueW(
+        HKEY_CURRENT_USER,
+        L"SOFTWARE\\Valve\\Steam",
+        name,
+        RRF_RT_REG_DWORD,
+        NULL,
+        value,
+        &size);
+    return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){READ_STEAM_REGISTRY, status};
+}
 
-    steam_process_iter_t iter;
-    DWORD iter_result = steam_process_iter_init(&iter, dir, dir_len);
-    if (iter_result != ERROR_SUCCESS) return (result_t){ENUM_PROCESSES, iter_result};
+/// note: name must be NUL terminated.
+result_t steam_registry_set_dword(const wchar_t *name, DWORD value) {
+    LSTATUS status = RegSetKeyValueW(
+        HKEY_CURRENT_USER,
+        L"SOFTWARE\\Valve\\Steam",
+        name,
+        REG_DWORD,
+        &value,
+        sizeof(value));
+    return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){WRITE_STEAM_REGISTRY, status};
+}
 
-    steam_process_t process = steam_process_iter_next(&iter);
-    if (process.pid) {
-        CloseHandle(process.handle);
-        *is_running = 1;
-    }
+result_t steam_get_active_user(uint32_t *account_id) {
+    DWORD value = 0;
+    DWORD size = sizeof(value);
+    LSTATUS status = RegGetValueW(
+        HKEY_CURRENT_USER,
+        L"SOFTWARE\\Valve\\Steam\\ActiveProcess",
+        L"ActiveUser",
+        RRF_RT_REG_DWORD,
+        NULL,
+        &value,
+        &size);
+    *account_id = value;
+    return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){READ_STEAM_REGISTRY, status};
+}
 
+typedef struct {
+    HKEY key;
+    HANDLE event;
+} registry_watch_t;
+
+/// checks Steam's registry subkey can be opened for reading, and then for writing.
+result_t steam_registry_check_access(void) {
+    HKEY key;
+    LSTATUS status = RegOpenKeyExW(HKEY_CURRENT_USER, L"SOFTWARE\\Valve\\Steam", 0, KEY_READ, &key);
+    if (status != ERROR_SUCCESS) return (result_t){READ_STEAM_REGISTRY, status};
+    RegCloseKey(key);
+    status = RegOpenKeyExW(HKEY_CURRENT_USER, L"SOFTWARE\\Valve\\Steam", 0, KEY_WRITE, &key);
+    if (status != ERROR_SUCCESS) return (result_t){WRITE_STEAM_REGISTRY, status};
+    RegCloseKey(key);
     return SUCCESS;
 }
 
+result_t steam_registry_watch_init(registry_watch_t *watch) {
+    LSTATUS status = RegOpenKeyExW(
+        HKEY_CURRENT_USER,
+        L"SOFTWARE\\Valve\\Steam",
+        0,
+        KEY_NOTIFY,
+        &watch->key);
+    if (status != ERROR_SUCCESS) return (result_t){WATCH_STEAM_REGISTRY, status};
+    watch->event = CreateEventW(NULL, FALSE, FALSE, NULL);
+    if (watch->event == NULL) {
+        const result_t result = FAILURE(WATCH_STEAM_REGISTRY);
+        RegCloseKey(watch->key);
+        return result;
+    }
+    return SUCCESS;
+}
+
+/// note: the watch is cancelled if the calling thread exits.
+result_t steam_registry_watch_arm(registry_watch_t const *watch) {
+    LSTATUS status = RegNotifyChangeKeyValue(
+        watch->key,
+        FALSE,
+        REG_NOTIFY_CHANGE_LAST_SET,
+        watch->event,
+        TRUE);
+    return (status == ERROR_SUCCESS) ? SUCCESS : (result_t){WATCH_STEAM_REGISTRY, status};
+}
+
+result_t steam_registry_watch_wait(registry_watch_t const *watch, DWORD timeout_ms, uint8_t *changed) {
+    const DWORD wait_result = WaitForSingleObject(watch->event, timeout_ms);
+    *changed = wait_result == WAIT_OBJECT_0;
+    return wait_result == WAIT_FAILED ? FAILURE(WATCH_STEAM_REGISTRY) : SUCCESS;
+}
+
+void steam_registry_watch_close(registry_watch_t *watch) {
+    CloseHandle(watch->event);
+    RegCloseKey(watch->key);
+}
+
 result_t steam_vdf_loginusers(const steam_t *steam, HANDLE* file) {
     wchar_t path[MAX_PATH];
     const size_t dir_len = steam_dir_lowercase(steam, path);
@@ -244,4 +331,26 @@ result_t steam_vdf_loginusers(const steam_t *steam, HANDLE* file) {
         NULL
     );
     return *file != INVALID_HANDLE_VALUE ? SUCCESS : FAILURE(OPEN_VDF);
-}
\ No newline at end of file
+}
+result_t steam_client_version(const steam_t *steam, uint32_t version[2]) {
+    DWORD handle = 0;
+    const DWORD size = GetFileVersionInfoSizeW(steam->path, &handle);
+    if (size == 0) return FAILURE(READ_VERSION);
+    void *info = HeapAlloc(GetProcessHeap(), 0, size);
+    if (info == NULL) return (result_t){READ_VERSION, ERROR_NOT_ENOUGH_MEMORY};
+    if (!GetFileVersionInfoW(steam->path, 0, size, info)) {
+        const result_t result = FAILURE(READ_VERSION);
+        HeapFree(GetProcessHeap(), 0, info);
+        return result;
+    }
+    VS_FIXEDFILEINFO *fixed = NULL;
+    UINT fixed_len = 0;
+    if (!VerQueryValueW(info, L"\\", (void**)&fixed, &fixed_len) || fixed_len < sizeof(*fixed)) {
+        HeapFree(GetProcessHeap(), 0, info);
+        return (result_t){READ_VERSION, ERROR_RESOURCE_TYPE_NOT_FOUND};
+    }
+    version[0] = fixed->dwFileVersionMS;
+    version[1] = fixed->dwFileVersionLS;
+    HeapFree(GetProcessHeap(), 0, info);
+    return SUCCESS;
+}